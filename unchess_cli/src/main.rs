@@ -4,6 +4,7 @@ use rustyline::{DefaultEditor, error::ReadlineError};
 
 use unchess_lib::{
     board::piece_list::PieceListBoard,
+    enums::{DrawReason, GameOutcome},
     error::ChessError,
     notation,
     simple_types::SimpleSquare,
@@ -67,7 +68,7 @@ impl Repl {
             "check" => self.check_move(command.1.get_one::<String>("PGN").unwrap())?,
             "get" => self.get_moves(command.1.get_one::<String>("SQUARE").unwrap())?,
             "show" => self.show_board(),
-            "print-fen" => println!("{}", self.board.as_fen_str()?),
+            "print-fen" => println!("{}", self.board.to_fen()),
             "quit" => return Ok(true),
             _ => unreachable!(),
         }
@@ -164,6 +165,13 @@ impl Repl {
             unchess_lib::enums::BoardState::Stalemate => println!("{}", "Stalemate!".bold()),
             unchess_lib::enums::BoardState::Checkmate => println!("{}", "Checkmate!".red().bold()),
         }
+        match self.board.outcome()? {
+            GameOutcome::ClaimableDraw(reason) => println!("{}", format!("Draw can be claimed: {reason:?}").bold()),
+            GameOutcome::Draw(reason) if reason != DrawReason::Stalemate => {
+                println!("{}", format!("Draw: {reason:?}").bold())
+            }
+            _ => (),
+        }
         Ok(())
     }
 
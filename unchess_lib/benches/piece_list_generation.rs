@@ -63,7 +63,7 @@ fn criterion_benchmark(c: &mut Criterion) {
     c.bench_function("Unchecked moving", |b| b.iter(|| play_unchecked_moves(&moves)));
     c.bench_function("Legal move generation", |b| b.iter(|| generate_checked_moves(&moves)));
     c.bench_function("Pseudo-legal move generation", |b| {
-        b.iter(|| generate_pchecked_moves(&moves))
+        b.iter(|| generate_pchecked_moves(&moves));
     });
 }
 
@@ -0,0 +1,218 @@
+//! Magic bitboard lookup tables for sliding piece attacks
+//!
+//! For every square the occupancy of the squares a rook or bishop could be blocked on is multiplied
+//! by a "magic" number, which maps every possible occupancy onto a small index into a table of
+//! precomputed attacks. The magic numbers were found by a random search for multipliers that don't
+//! map two occupancies with different attacks onto the same index, and the tables are filled in
+//! when they are first used.
+
+use std::iter;
+use std::sync::LazyLock;
+
+use super::BitSquare;
+use crate::traits::ChessSquare as _;
+
+const BISHOP_DIRECTIONS: [(i8, i8); 4] = [(-1, -1), (-1, 1), (1, -1), (1, 1)];
+
+const ROOK_DIRECTIONS: [(i8, i8); 4] = [(0, -1), (0, 1), (-1, 0), (1, 0)];
+
+#[rustfmt::skip]
+const BISHOP_MAGICS: [u64; 64] = [
+    0x8020_0830_0080_8410, 0x0910_0102_0082_0401, 0x4848_1085_0a00_2040, 0x0088_0a00_2810_0404,
+    0x0601_1140_0010_8000, 0x3002_2220_6001_0000, 0x3004_2218_0404_0000, 0x0100_2428_0808_0812,
+    0x2210_2061_2101_0101, 0x4002_1082_1081_2602, 0x1000_0404_0400_5800, 0x0040_0444_0188_0c64,
+    0x0000_2110_4040_0020, 0x0000_0082_2020_8400, 0x0304_0041_1821_1000, 0x2904_0824_0402_8800,
+    0x0020_4040_1202_2e00, 0x0302_0020_8821_0100, 0x0010_4002_0404_0022, 0x0048_0684_0211_2000,
+    0x0041_0088_2008_4000, 0x0000_8003_0260_0212, 0x0002_0d4e_4804_4400, 0x4000_4400_2202_1000,
+    0x0190_4040_0842_0460, 0x0042_2008_1104_1080, 0x0804_0400_4245_0400, 0x0004_0800_3401_2002,
+    0x0204_0820_1400_2000, 0x0000_4600_0041_1020, 0x2052_1204_26a2_9018, 0x2009_0100_8038_8800,
+    0x5110_1129_0010_2200, 0x0408_0210_4888_2101, 0x0181_4430_0002_0404, 0x4020_2008_0205_0105,
+    0x0000_5900_4004_0040, 0x1520_2401_0000_2080, 0x4148_5602_6000_8800, 0x0080_a083_0442_0110,
+    0x0001_0402_2003_4018, 0x0404_2084_0404_1080, 0x0001_1018_0400_0800, 0x0141_0040_1040_0200,
+    0x8200_0102_1200_4400, 0x0002_2090_0081_0900, 0x0020_4800_c100_2080, 0x0038_0810_8821_8094,
+    0x4a0c_5110_1012_0100, 0x1010_9401_1110_0000, 0x0100_8034_0228_0104, 0x0001_2714_2088_0100,
+    0x0280_0008_2101_0000, 0x0000_c109_1109_0021, 0x0004_1102_2204_0010, 0x1104_5000_8921_0200,
+    0x0153_0025_8620_1020, 0x0008_0244_4410_0850, 0x1000_0040_8048_0808, 0x4080_0060_0194_1400,
+    0x0000_0900_1020_4840, 0x0014_4008_1010_8083, 0x0060_0802_2418_2203, 0x0250_0420_840c_4200,
+];
+
+#[rustfmt::skip]
+const ROOK_MAGICS: [u64; 64] = [
+    0x5880_1020_8000_4000, 0xc0c0_00c0_9000_2000, 0x8100_2000_1009_0042, 0x0480_0490_0008_0080,
+    0x0100_1100_0402_0800, 0x0200_0401_0200_1088, 0x0200_028c_0200_4128, 0x0100_0142_0230_8100,
+    0x0820_8000_4000_8020, 0x0a02_8020_0480_4005, 0x0001_8080_2000_9000, 0x1001_0010_0020_0902,
+    0x0480_8008_0080_0400, 0x1844_8004_0080_1200, 0x1104_0001_0204_9850, 0x2001_0000_aa10_4100,
+    0x4080_0040_4000_2000, 0x0120_8080_4000_2000, 0x0041_0100_2000_1044, 0x2310_0180_0800_1082,
+    0x6440_8080_0400_0800, 0x9000_8080_0400_0201, 0x0840_0400_0208_c110, 0x0000_0200_0c42_8324,
+    0x0880_0040_4000_2000, 0x2002_0042_0020_8100, 0x1081_0011_0040_2000, 0x0908_0208_8010_0080,
+    0x0001_0211_0004_0800, 0x220a_0004_0400_2010, 0x100c_0204_0010_8801, 0x0001_0882_0006_4421,
+    0x0880_0040_0140_2000, 0x0100_2001_4240_1000, 0x8380_1082_0200_2042, 0x1501_0020_0900_1000,
+    0x2040_8004_0280_0800, 0x8822_0010_0404_0020, 0x0082_0008_0200_4144, 0x1108_2084_0600_005d,
+    0x0080_8040_0022_8003, 0x0020_4220_1002_4008, 0x00a0_0402_0010_1000, 0x2040_4200_0812_0020,
+    0x8008_0100_0409_0010, 0x0012_0020_1004_0400, 0x0820_2a08_4114_0010, 0x5124_0071_0082_0004,
+    0x0412_c000_2580_0080, 0x4020_4200_8100_2200, 0x0000_1000_2000_8480, 0x0000_2101_1000_0900,
+    0x1006_0008_2004_1200, 0x0024_0400_0200_8080, 0x0000_080a_0910_2c00, 0x0040_2408_4108_8200,
+    0x2400_4080_0020_1109, 0x0440_8040_0010_2901, 0x0000_2010_0840_8202, 0x0008_0d08_a030_0101,
+    0x0002_0008_1120_0482, 0x0002_0018_03a4_1002, 0x0414_1a04_9028_2104, 0x1030_0114_0820_8042,
+];
+
+static BISHOP_TABLE: LazyLock<MagicTable> = LazyLock::new(|| MagicTable::new(&BISHOP_DIRECTIONS, &BISHOP_MAGICS));
+
+static ROOK_TABLE: LazyLock<MagicTable> = LazyLock::new(|| MagicTable::new(&ROOK_DIRECTIONS, &ROOK_MAGICS));
+
+/// Squares attacked by a bishop on `square`
+pub fn bishop_attacks(square: BitSquare, occupied: u64) -> u64 {
+    BISHOP_TABLE.attacks(square, occupied)
+}
+
+/// Squares attacked by a rook on `square`
+pub fn rook_attacks(square: BitSquare, occupied: u64) -> u64 {
+    ROOK_TABLE.attacks(square, occupied)
+}
+
+/// Squares attacked from `square` by a piece sliding in `directions`, stopping at occupied squares
+///
+/// Walks each ray one square at a time, so is only used to build and check the lookup tables.
+fn sliding_attacks(square: BitSquare, occupied: u64, directions: &[(i8, i8)]) -> u64 {
+    let mut attacks = 0;
+    for (file_step, rank_step) in directions {
+        let mut file = square.file() as i8;
+        let mut rank = square.rank() as i8;
+        loop {
+            file += file_step;
+            rank += rank_step;
+            if !(0..8).contains(&file) || !(0..8).contains(&rank) {
+                break;
+            }
+            let bit = 1 << (rank * 8 + file);
+            attacks |= bit;
+            if occupied & bit != 0 {
+                break;
+            }
+        }
+    }
+    attacks
+}
+
+/// Squares whose occupancy can change the attacks from `square`
+///
+/// The last square of each ray is left out since a piece there doesn't block anything further.
+fn relevant_occupancy(square: BitSquare, directions: &[(i8, i8)]) -> u64 {
+    let mut mask = 0;
+    for (file_step, rank_step) in directions {
+        let mut file = square.file() as i8 + file_step;
+        let mut rank = square.rank() as i8 + rank_step;
+        while (0..8).contains(&(file + file_step)) && (0..8).contains(&(rank + rank_step)) {
+            mask |= 1 << (rank * 8 + file);
+            file += file_step;
+            rank += rank_step;
+        }
+    }
+    mask
+}
+
+/// Iterator over every subset of the bits in `mask`, starting with the empty set
+fn subsets(mask: u64) -> impl Iterator<Item = u64> {
+    let mut subset = Some(0u64);
+    iter::from_fn(move || {
+        let current = subset?;
+        let next = current.wrapping_sub(mask) & mask;
+        subset = (next != 0).then_some(next);
+        Some(current)
+    })
+}
+
+/// Lookup parameters for a single square
+#[derive(Debug, Copy, Clone, Default)]
+struct MagicEntry {
+    mask: u64,
+    magic: u64,
+    shift: u32,
+    offset: usize,
+}
+
+impl MagicEntry {
+    fn index(&self, occupied: u64) -> usize {
+        self.offset + ((occupied & self.mask).wrapping_mul(self.magic) >> self.shift) as usize
+    }
+}
+
+/// Attack lookup tables for one kind of sliding piece
+#[derive(Debug)]
+struct MagicTable {
+    magics: [MagicEntry; 64],
+    attacks: Vec<u64>,
+}
+
+impl MagicTable {
+    fn new(directions: &[(i8, i8)], magics: &[u64; 64]) -> Self {
+        let mut entries = [MagicEntry::default(); 64];
+        let mut attacks = vec![];
+        for (index, entry) in entries.iter_mut().enumerate() {
+            let square = BitSquare(index as u8);
+            let mask = relevant_occupancy(square, directions);
+            *entry = MagicEntry {
+                mask,
+                magic: magics[index],
+                shift: 64 - mask.count_ones(),
+                offset: attacks.len(),
+            };
+            attacks.resize(attacks.len() + (1 << mask.count_ones()), 0);
+            for occupied in subsets(mask) {
+                attacks[entry.index(occupied)] = sliding_attacks(square, occupied, directions);
+            }
+        }
+        Self {
+            magics: entries,
+            attacks,
+        }
+    }
+
+    fn attacks(&self, square: BitSquare, occupied: u64) -> u64 {
+        self.attacks[self.magics[square.index() as usize].index(occupied)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_table(directions: &[(i8, i8)], lookup: fn(BitSquare, u64) -> u64) {
+        for index in 0..64 {
+            let square = BitSquare(index);
+            let mask = relevant_occupancy(square, directions);
+            for occupied in subsets(mask) {
+                assert_eq!(lookup(square, occupied), sliding_attacks(square, occupied, directions));
+                // Pieces outside the relevant squares must never change the attacks
+                let outside = !mask & !square.bitboard();
+                assert_eq!(
+                    lookup(square, occupied | outside),
+                    sliding_attacks(square, occupied | outside, directions)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn subset_count() {
+        assert_eq!(subsets(0).count(), 1);
+        assert_eq!(subsets(0b1011_0000).count(), 8);
+    }
+
+    #[test]
+    fn relevant_occupancy_corner() {
+        let a1 = BitSquare::new(0, 0);
+        assert_eq!(relevant_occupancy(a1, &ROOK_DIRECTIONS).count_ones(), 12);
+        assert_eq!(relevant_occupancy(a1, &BISHOP_DIRECTIONS).count_ones(), 6);
+    }
+
+    #[test]
+    fn bishop_table() {
+        check_table(&BISHOP_DIRECTIONS, bishop_attacks);
+    }
+
+    #[test]
+    fn rook_table() {
+        check_table(&ROOK_DIRECTIONS, rook_attacks);
+    }
+}
@@ -0,0 +1,1142 @@
+//! Bitboard representation of chess board
+//!
+//! Uses u64s with a bit to represent each square on the chess board, bit 0 representing square a1
+//! and bit 64 representing square h8. This is the most performant implementation of a chess board
+//! for almost all uses.
+//!
+//! Sliding piece attacks are looked up in magic bitboard tables, see the `magic` module.
+
+mod magic;
+
+use core::fmt;
+
+use magic::{bishop_attacks, rook_attacks};
+
+use crate::board::{castling, dead_position, zobrist};
+use crate::enums::{AmbiguousMove, BoardState, CastlingSide, PieceColour, PieceKind};
+use crate::error::{ChessError, PositionError};
+use crate::notation::fen::{Fen, castling_index};
+use crate::parser::pgn;
+use crate::simple_types::{SimpleMove, SimplePiece, SimpleSquare};
+use crate::traits::{
+    ChessBoard as _, ChessMove as _, ChessPiece as _, ChessSquare as _, LegalMoveGenerator, PLegalMoveGenerator,
+};
+use crate::{notation, traits};
+
+/// Chess square
+///
+/// Internally represented as the index of the square's bit, 0 representing a1 and 63 representing
+/// h8.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BitSquare(u8);
+
+impl traits::ChessSquare for BitSquare {
+    fn file(&self) -> u8 {
+        self.0 % 8
+    }
+
+    fn rank(&self) -> u8 {
+        self.0 / 8
+    }
+}
+
+impl fmt::Display for BitSquare {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl From<SimpleSquare> for BitSquare {
+    fn from(value: SimpleSquare) -> Self {
+        Self(value.rank() * 8 + value.file())
+    }
+}
+
+impl From<BitSquare> for SimpleSquare {
+    fn from(value: BitSquare) -> Self {
+        SimpleSquare::new(value.file(), value.rank())
+    }
+}
+
+impl BitSquare {
+    /// Chess square at `file` and `rank`
+    ///
+    /// # Panics
+    /// Panics if file and/or rank are not between 0-7 inclusive
+    pub fn new(file: u8, rank: u8) -> Self {
+        assert!((0..8).contains(&file), "File must be between 0-7 inclusive, {file} > 7");
+        assert!((0..8).contains(&rank), "Rank must be between 0-7 inclusive, {rank} > 7");
+        Self(rank * 8 + file)
+    }
+
+    /// Index of the square's bit, 0 representing a1 and 63 representing h8
+    pub fn index(&self) -> u8 {
+        self.0
+    }
+
+    /// Bitboard with only this square set
+    pub fn bitboard(&self) -> u64 {
+        1 << self.0
+    }
+
+    /// Create square from PGN standard string
+    ///
+    /// # Errors
+    /// [`crate::error::ChessError::InvalidPGN`] if input is invalid
+    pub fn from_pgn_str(input: &str) -> Result<Self, ChessError> {
+        SimpleSquare::from_pgn_str(input).map(Self::from)
+    }
+}
+
+/// Iterator over the set bits of a bitboard, from least to most significant
+struct Squares(u64);
+
+impl Iterator for Squares {
+    type Item = BitSquare;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.0 == 0 {
+            None
+        } else {
+            let index = self.0.trailing_zeros() as u8;
+            self.0 &= self.0 - 1;
+            Some(BitSquare(index))
+        }
+    }
+}
+
+/// Chess piece representation
+///
+/// Only contains the kind and colour of the piece, the position is implied by which bitboard it was
+/// read from.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BitPiece {
+    kind: PieceKind,
+    colour: PieceColour,
+}
+
+impl traits::ChessPiece for BitPiece {
+    fn kind(&self) -> PieceKind {
+        self.kind
+    }
+
+    fn colour(&self) -> PieceColour {
+        self.colour
+    }
+}
+
+impl fmt::Display for BitPiece {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_fen())
+    }
+}
+
+impl From<BitPiece> for SimplePiece {
+    fn from(value: BitPiece) -> Self {
+        SimplePiece::new(value.kind, value.colour)
+    }
+}
+
+impl BitPiece {
+    /// Chess piece
+    pub fn new(kind: PieceKind, colour: PieceColour) -> Self {
+        Self { kind, colour }
+    }
+}
+
+/// Piece kinds in the order of their bitboards
+const PIECE_KINDS: [PieceKind; 6] = [
+    PieceKind::King,
+    PieceKind::Queen,
+    PieceKind::Bishop,
+    PieceKind::Knight,
+    PieceKind::Rook,
+    PieceKind::Pawn,
+];
+
+/// Chess move from src to dest
+///
+/// Packed into a u16, bits 0-5 hold the source square, bits 6-11 the destination square and bits
+/// 12-14 the piece to promote to.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BitMove(u16);
+
+impl traits::ChessMove for BitMove {
+    type Square = BitSquare;
+
+    fn src(&self) -> BitSquare {
+        BitSquare((self.0 & 0x3f) as u8)
+    }
+
+    fn dest(&self) -> BitSquare {
+        BitSquare(((self.0 >> 6) & 0x3f) as u8)
+    }
+
+    fn promote_to(&self) -> Option<PieceKind> {
+        match self.0 >> 12 {
+            0 => None,
+            kind => Some(PIECE_KINDS[kind as usize - 1]),
+        }
+    }
+}
+
+impl fmt::Display for BitMove {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl From<SimpleMove> for BitMove {
+    fn from(value: SimpleMove) -> Self {
+        Self::new(value.src().into(), value.dest().into(), value.promote_to())
+    }
+}
+
+impl From<BitMove> for SimpleMove {
+    fn from(value: BitMove) -> Self {
+        SimpleMove::new(value.src().into(), value.dest().into(), value.promote_to())
+    }
+}
+
+impl BitMove {
+    /// Chess move from `src` to `dest`
+    ///
+    /// # Panics
+    /// Panics if source and destination are the same square
+    pub fn new(src: BitSquare, dest: BitSquare, promote_to: Option<PieceKind>) -> Self {
+        assert_ne!(src, dest, "Chess move cannot originate and terminate at same square");
+        let promotion = promote_to.map_or(0, |kind| kind as u16 + 1);
+        Self(u16::from(src.0) | (u16::from(dest.0) << 6) | (promotion << 12))
+    }
+
+    /// Create move from PGNish string, but unambiguous
+    ///
+    /// # Errors
+    /// [crate::error::ChessError::InvalidPGN] if `pgn` is invalid
+    pub fn from_pgn_str(pgn: &str) -> Result<Self, ChessError> {
+        if let Ok(m) = pgn::unambiguous_move(pgn) {
+            Ok(m.1.into())
+        } else {
+            Err(ChessError::InvalidPGN(pgn.to_string()))
+        }
+    }
+}
+
+/// Bitboard with every square reachable from each square using `offsets`
+const fn offset_table(offsets: &[(i8, i8)]) -> [u64; 64] {
+    let mut table = [0; 64];
+    let mut square = 0;
+    while square < 64 {
+        let mut i = 0;
+        while i < offsets.len() {
+            let file = (square % 8) as i8 + offsets[i].0;
+            let rank = (square / 8) as i8 + offsets[i].1;
+            if file >= 0 && file < 8 && rank >= 0 && rank < 8 {
+                table[square] |= 1 << (rank * 8 + file);
+            }
+            i += 1;
+        }
+        square += 1;
+    }
+    table
+}
+
+const KNIGHT_ATTACKS: [u64; 64] =
+    offset_table(&[(-2, -1), (-2, 1), (-1, -2), (-1, 2), (1, -2), (1, 2), (2, -1), (2, 1)]);
+
+const KING_ATTACKS: [u64; 64] = offset_table(&[(-1, -1), (-1, 1), (1, -1), (1, 1), (0, -1), (0, 1), (-1, 0), (1, 0)]);
+
+/// Squares attacked by a pawn on each square, indexed by colour
+const PAWN_ATTACKS: [[u64; 64]; 2] = [offset_table(&[(-1, -1), (1, -1)]), offset_table(&[(-1, 1), (1, 1)])];
+
+/// Bitboard of the light squares, b1, d1 and so on
+const LIGHT_SQUARES: u64 = 0x55aa_55aa_55aa_55aa;
+/// Squares on the first and last ranks
+const BACK_RANKS: u64 = 0xff00_0000_0000_00ff;
+
+/// Placement of the pieces on the board
+///
+/// Kept separate from the rest of the board state so that moves can be tried out on a copy without
+/// cloning the board history.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct Layout {
+    /// Bitboard for each piece kind, indexed by [`PieceKind`]
+    pieces: [u64; 6],
+    /// Bitboard for each colour, indexed by [`PieceColour`]
+    colours: [u64; 2],
+    /// Zobrist hash of the pieces, kept up to date as pieces are toggled
+    hash: u64,
+}
+
+impl Layout {
+    fn occupied(&self) -> u64 {
+        self.colours[0] | self.colours[1]
+    }
+
+    fn pieces_of(&self, kind: PieceKind, colour: PieceColour) -> u64 {
+        self.pieces[kind as usize] & self.colours[colour as usize]
+    }
+
+    fn piece_at(&self, square: BitSquare) -> Option<BitPiece> {
+        let bit = square.bitboard();
+        let colour = if self.colours[PieceColour::White as usize] & bit != 0 {
+            PieceColour::White
+        } else if self.colours[PieceColour::Black as usize] & bit != 0 {
+            PieceColour::Black
+        } else {
+            return None;
+        };
+        PIECE_KINDS
+            .into_iter()
+            .find(|&kind| self.pieces[kind as usize] & bit != 0)
+            .map(|kind| BitPiece::new(kind, colour))
+    }
+
+    fn toggle_piece(&mut self, square: BitSquare, piece: BitPiece) {
+        self.pieces[piece.kind as usize] ^= square.bitboard();
+        self.colours[piece.colour as usize] ^= square.bitboard();
+        self.hash ^= zobrist::piece_key(&piece, &square);
+    }
+
+    fn king_square(&self, colour: PieceColour) -> Result<BitSquare, ChessError> {
+        let kings = self.pieces_of(PieceKind::King, colour);
+        if kings.is_power_of_two() {
+            Ok(BitSquare(kings.trailing_zeros() as u8))
+        } else {
+            Err(PositionError::KingCount {
+                colour,
+                count: kings.count_ones() as usize,
+            }
+            .into())
+        }
+    }
+
+    /// Checks if `square` is attacked by any piece of colour `colour`
+    fn square_attacked(&self, square: BitSquare, colour: PieceColour) -> bool {
+        let occupied = self.occupied();
+        let index = square.index() as usize;
+        let queens = self.pieces_of(PieceKind::Queen, colour);
+        KNIGHT_ATTACKS[index] & self.pieces_of(PieceKind::Knight, colour) != 0
+            || KING_ATTACKS[index] & self.pieces_of(PieceKind::King, colour) != 0
+            || PAWN_ATTACKS[!colour as usize][index] & self.pieces_of(PieceKind::Pawn, colour) != 0
+            || bishop_attacks(square, occupied) & (self.pieces_of(PieceKind::Bishop, colour) | queens) != 0
+            || rook_attacks(square, occupied) & (self.pieces_of(PieceKind::Rook, colour) | queens) != 0
+    }
+
+    /// Move pieces according to `chess_move`, including en passant captures and castling rooks
+    ///
+    /// Returns the piece that moved, and the piece that was taken along with the square it was
+    /// taken from.
+    fn apply_move(
+        &mut self,
+        chess_move: BitMove,
+        en_passant: Option<BitSquare>,
+        castling_rights: [Option<u8>; 4],
+    ) -> Result<(BitPiece, Option<(BitSquare, BitPiece)>), ChessError> {
+        let src = chess_move.src();
+        let dest = chess_move.dest();
+        let piece = self
+            .piece_at(src)
+            .ok_or_else(|| ChessError::PieceNotFound(src.into()))?;
+        if let Some((rook_src, rook_dest, king_dest)) = Self::castling_squares(piece, chess_move, castling_rights) {
+            let rook = BitPiece::new(PieceKind::Rook, piece.colour);
+            if self.piece_at(rook_src) != Some(rook) {
+                return Err(ChessError::PieceNotFound(rook_src.into()));
+            }
+            // Take both off before putting them back, as either may land where the other started
+            self.toggle_piece(src, piece);
+            self.toggle_piece(rook_src, rook);
+            self.toggle_piece(king_dest, piece);
+            self.toggle_piece(rook_dest, rook);
+            return Ok((piece, None));
+        }
+        let mut taken = self.piece_at(dest).map(|taken_piece| (dest, taken_piece));
+
+        if piece.kind == PieceKind::Pawn && Some(dest) == en_passant && taken.is_none() {
+            let taken_pawn_square = BitSquare::new(dest.file(), src.rank());
+            match self.piece_at(taken_pawn_square) {
+                Some(taken_pawn) => taken = Some((taken_pawn_square, taken_pawn)),
+                None => return Err(PositionError::ImpossibleEnPassant(dest.into()).into()),
+            }
+        }
+
+        if let Some((square, taken_piece)) = taken {
+            self.toggle_piece(square, taken_piece);
+        }
+        self.toggle_piece(src, piece);
+        self.toggle_piece(
+            dest,
+            BitPiece::new(chess_move.promote_to().unwrap_or(piece.kind), piece.colour),
+        );
+        Ok((piece, taken))
+    }
+
+    /// Reverse [`Layout::apply_move`], given the piece that moved, the piece that was taken and the
+    /// castling rights before the move
+    fn unapply_move(
+        &mut self,
+        chess_move: BitMove,
+        piece: BitPiece,
+        taken: Option<(BitSquare, BitPiece)>,
+        castling_rights: [Option<u8>; 4],
+    ) {
+        if let Some((rook_src, rook_dest, king_dest)) = Self::castling_squares(piece, chess_move, castling_rights) {
+            let rook = BitPiece::new(PieceKind::Rook, piece.colour);
+            self.toggle_piece(king_dest, piece);
+            self.toggle_piece(rook_dest, rook);
+            self.toggle_piece(chess_move.src(), piece);
+            self.toggle_piece(rook_src, rook);
+            return;
+        }
+        self.toggle_piece(
+            chess_move.dest(),
+            BitPiece::new(chess_move.promote_to().unwrap_or(piece.kind), piece.colour),
+        );
+        self.toggle_piece(chess_move.src(), piece);
+        if let Some((square, taken_piece)) = taken {
+            self.toggle_piece(square, taken_piece);
+        }
+    }
+
+    /// Source and destination of the rook and destination of the king if `piece` making
+    /// `chess_move` is castling
+    fn castling_squares(
+        piece: BitPiece,
+        chess_move: BitMove,
+        castling_rights: [Option<u8>; 4],
+    ) -> Option<(BitSquare, BitSquare, BitSquare)> {
+        if piece.kind != PieceKind::King {
+            return None;
+        }
+        let src = chess_move.src();
+        let (side, rook_file) = castling::castle(castling_rights, piece.colour, &src, &chess_move.dest())?;
+        let (king_file, rook_final) = castling::final_files(side);
+        Some((
+            BitSquare::new(rook_file, src.rank()),
+            BitSquare::new(rook_final, src.rank()),
+            BitSquare::new(king_file, src.rank()),
+        ))
+    }
+}
+
+impl From<&[[Option<SimplePiece>; 8]; 8]> for Layout {
+    fn from(value: &[[Option<SimplePiece>; 8]; 8]) -> Self {
+        let mut layout = Self {
+            pieces: [0; 6],
+            colours: [0; 2],
+            hash: 0,
+        };
+        for (i, rank) in value.iter().enumerate() {
+            for (j, piece) in rank.iter().enumerate() {
+                if let Some(piece) = piece {
+                    layout.toggle_piece(
+                        BitSquare::new(j as u8, 7 - i as u8),
+                        BitPiece::new(piece.kind(), piece.colour()),
+                    );
+                }
+            }
+        }
+        layout
+    }
+}
+
+/// What was played on a [`BitBoard`], kept to take it back
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum UndoMove {
+    /// Move of a piece already on the board
+    Board(BitMove),
+    /// Piece dropped on the square from a Crazyhouse pocket
+    Drop(BitSquare),
+}
+
+/// Information needed to take back a move on a [`BitBoard`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct BitUndo {
+    chess_move: UndoMove,
+    piece: BitPiece,
+    taken: Option<(BitSquare, BitPiece)>,
+    en_passant: Option<BitSquare>,
+    castling_rights: [Option<u8>; 4],
+    halfmove_clock: u32,
+}
+
+/// Bitboard representation of chess board
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct BitBoard {
+    layout: Layout,
+    turn: PieceColour,
+    en_passant: Option<BitSquare>,
+    castling_rights: [Option<u8>; 4],
+    halfmove_clock: u32,
+    fullmove_number: u32,
+    /// Hashes of every earlier position, oldest first
+    board_history: Vec<u64>,
+}
+
+impl traits::ChessBoard for BitBoard {
+    type Square = BitSquare;
+    type Piece = BitPiece;
+    type Move = BitMove;
+    type Undo = BitUndo;
+
+    fn to_fen(&self) -> Fen {
+        Fen::from(self)
+    }
+
+    fn get_piece(&self, square: BitSquare) -> Result<BitPiece, ChessError> {
+        self.layout
+            .piece_at(square)
+            .ok_or_else(|| ChessError::PieceNotFound(square.into()))
+    }
+
+    fn all_pieces(&self) -> impl IntoIterator<Item = BitPiece> {
+        Squares(self.layout.occupied()).filter_map(|square| self.layout.piece_at(square))
+    }
+
+    fn turn(&self) -> PieceColour {
+        self.turn
+    }
+
+    fn halfmove_clock(&self) -> u32 {
+        self.halfmove_clock
+    }
+
+    fn repetition_count(&self) -> usize {
+        zobrist::repetitions(&self.board_history, self.hash(), self.halfmove_clock) + 1
+    }
+
+    fn insufficient_material(&self) -> bool {
+        let minors = self.layout.pieces[PieceKind::Bishop as usize] | self.layout.pieces[PieceKind::Knight as usize];
+        let pieces = self.layout.occupied() & !self.layout.pieces[PieceKind::King as usize];
+        if pieces & !minors != 0 {
+            false
+        } else if pieces.count_ones() <= 1 {
+            true
+        } else {
+            pieces & self.layout.pieces[PieceKind::Knight as usize] == 0
+                && (pieces & LIGHT_SQUARES == 0 || pieces & !LIGHT_SQUARES == 0)
+        }
+    }
+
+    fn is_dead_position(&self) -> bool {
+        let pawns = self.layout.pieces[PieceKind::Pawn as usize];
+        let kings = self.layout.pieces[PieceKind::King as usize];
+        let by_colour = |pieces: u64| self.layout.colours.map(|colour| colour & pieces);
+        self.insufficient_material()
+            || (self.en_passant.is_none()
+                && dead_position::pawns_locked(
+                    by_colour(pawns),
+                    by_colour(kings),
+                    self.layout.occupied() & !pawns & !kings,
+                ))
+    }
+
+    fn hash(&self) -> u64 {
+        self.layout.hash ^ zobrist::state_key(self.turn, self.castling_rights, self.en_passant)
+    }
+
+    fn make_move(&mut self, chess_move: BitMove) -> Result<BitUndo, ChessError> {
+        let hash = self.hash();
+        let (piece, taken) = self
+            .layout
+            .apply_move(chess_move, self.en_passant, self.castling_rights)?;
+        self.board_history.push(hash);
+        let undo = BitUndo {
+            chess_move: UndoMove::Board(chess_move),
+            piece,
+            taken,
+            en_passant: self.en_passant,
+            castling_rights: self.castling_rights,
+            halfmove_clock: self.halfmove_clock,
+        };
+
+        self.halfmove_clock += 1;
+        if piece.kind == PieceKind::Pawn || taken.is_some() {
+            self.halfmove_clock = 0;
+        }
+
+        let src = chess_move.src();
+        let dest = chess_move.dest();
+        if piece.kind == PieceKind::Pawn && src.rank().abs_diff(dest.rank()) == 2 {
+            self.en_passant = Some(BitSquare::new(src.file(), (src.rank() + dest.rank()) / 2));
+        } else {
+            self.en_passant = None;
+        }
+        castling::revoke(&mut self.castling_rights, piece.kind, piece.colour, &src, &dest);
+
+        self.turn = !self.turn;
+        if self.turn == PieceColour::White {
+            self.fullmove_number += 1;
+        }
+        Ok(undo)
+    }
+
+    fn unmake_move(&mut self, undo: BitUndo) {
+        if self.turn == PieceColour::White {
+            self.fullmove_number -= 1;
+        }
+        self.turn = !self.turn;
+        match undo.chess_move {
+            UndoMove::Board(chess_move) => {
+                self.layout
+                    .unapply_move(chess_move, undo.piece, undo.taken, undo.castling_rights);
+            }
+            UndoMove::Drop(square) => self.layout.toggle_piece(square, undo.piece),
+        }
+        self.board_history.pop();
+        self.en_passant = undo.en_passant;
+        self.castling_rights = undo.castling_rights;
+        self.halfmove_clock = undo.halfmove_clock;
+    }
+}
+
+impl PLegalMoveGenerator for BitBoard {
+    fn all_plegal_moves(&self) -> Result<impl IntoIterator<Item = BitMove>, ChessError> {
+        let mut moves: Vec<BitMove> = Vec::with_capacity(64);
+        for square in Squares(self.layout.colours[self.turn as usize]) {
+            let piece = self.get_piece(square)?;
+            self.push_plegal_moves(square, piece, &mut moves);
+        }
+        Ok(moves)
+    }
+
+    fn piece_plegal_moves(&self, square: BitSquare) -> Result<impl IntoIterator<Item = BitMove>, ChessError> {
+        let piece = self.get_piece(square)?;
+        let mut moves: Vec<BitMove> = vec![];
+        if piece.colour == self.turn {
+            self.push_plegal_moves(square, piece, &mut moves);
+        }
+        Ok(moves)
+    }
+
+    fn is_move_plegal(&self, chess_move: BitMove) -> Result<bool, ChessError> {
+        Ok(self
+            .piece_plegal_moves(chess_move.src())?
+            .into_iter()
+            .any(|plegal_move| plegal_move == chess_move))
+    }
+
+    fn move_piece_plegal(&mut self, chess_move: BitMove) -> Result<(), ChessError> {
+        if self.is_move_plegal(chess_move)? {
+            self.move_piece(chess_move)
+        } else {
+            Err(ChessError::IllegalMove(chess_move.into()))
+        }
+    }
+}
+
+impl LegalMoveGenerator for BitBoard {
+    fn all_legal_moves(&self) -> Result<impl IntoIterator<Item = BitMove>, ChessError> {
+        let mut moves: Vec<BitMove> = vec![];
+        for chess_move in self.all_plegal_moves()? {
+            if !self.leaves_king_in_check(chess_move)? {
+                moves.push(chess_move);
+            }
+        }
+        Ok(moves)
+    }
+
+    fn piece_legal_moves(&self, square: BitSquare) -> Result<impl IntoIterator<Item = BitMove>, ChessError> {
+        let mut moves: Vec<BitMove> = vec![];
+        for chess_move in self.piece_plegal_moves(square)? {
+            if !self.leaves_king_in_check(chess_move)? {
+                moves.push(chess_move);
+            }
+        }
+        Ok(moves)
+    }
+
+    fn is_move_legal(&self, chess_move: BitMove) -> Result<bool, ChessError> {
+        Ok(self.is_move_plegal(chess_move)? && !self.leaves_king_in_check(chess_move)?)
+    }
+
+    fn move_piece_legal(&mut self, chess_move: BitMove) -> Result<(), ChessError> {
+        if self.is_move_legal(chess_move)? {
+            self.move_piece(chess_move)
+        } else {
+            Err(ChessError::IllegalMove(chess_move.into()))
+        }
+    }
+
+    fn state(&self) -> Result<BoardState, ChessError> {
+        match (
+            self.all_legal_moves()?.into_iter().next().is_none(),
+            self.king_in_check(self.turn)?,
+        ) {
+            (true, true) => Ok(BoardState::Checkmate),
+            (true, false) => Ok(BoardState::Stalemate),
+            (false, true) => Ok(BoardState::Check),
+            (false, false) => Ok(BoardState::Normal),
+        }
+    }
+
+    fn in_check(&self) -> Result<bool, ChessError> {
+        self.king_in_check(self.turn)
+    }
+
+    fn disambiguate_move(&self, chess_move: AmbiguousMove) -> Result<BitMove, ChessError> {
+        match chess_move {
+            AmbiguousMove::Normal { .. } => self.disambiguate_normal(chess_move),
+            AmbiguousMove::Castle { side, .. } => Ok(self.disambiguate_castling(side)),
+            // Pieces can only be dropped in Crazyhouse
+            AmbiguousMove::Drop { .. } => Err(ChessError::ImpossibleMove(chess_move)),
+        }
+    }
+}
+
+impl From<Fen> for BitBoard {
+    fn from(value: Fen) -> Self {
+        Self {
+            layout: Layout::from(&*value.layout),
+            turn: value.turn,
+            en_passant: value.en_passant.map(BitSquare::from),
+            castling_rights: value.castling_rights,
+            halfmove_clock: value.halfmove_clock,
+            fullmove_number: value.fullmove_number,
+            board_history: Vec::with_capacity(100),
+        }
+    }
+}
+
+impl BitBoard {
+    const fn back_rank(colour: PieceColour) -> u8 {
+        match colour {
+            PieceColour::Black => 7,
+            PieceColour::White => 0,
+        }
+    }
+
+    fn push_plegal_moves(&self, square: BitSquare, piece: BitPiece, moves: &mut Vec<BitMove>) {
+        let own = self.layout.colours[piece.colour as usize];
+        let occupied = self.layout.occupied();
+        let index = square.index() as usize;
+        let targets = match piece.kind {
+            PieceKind::King => {
+                self.push_castle_moves(square, piece.colour, moves);
+                KING_ATTACKS[index]
+            }
+            PieceKind::Queen => bishop_attacks(square, occupied) | rook_attacks(square, occupied),
+            PieceKind::Bishop => bishop_attacks(square, occupied),
+            PieceKind::Knight => KNIGHT_ATTACKS[index],
+            PieceKind::Rook => rook_attacks(square, occupied),
+            PieceKind::Pawn => {
+                self.push_pawn_moves(square, piece.colour, moves);
+                return;
+            }
+        };
+        moves.extend(Squares(targets & !own).map(|dest| BitMove::new(square, dest, None)));
+    }
+
+    fn push_pawn_moves(&self, square: BitSquare, colour: PieceColour, moves: &mut Vec<BitMove>) {
+        let occupied = self.layout.occupied();
+        let enemies = self.layout.colours[!colour as usize] | self.en_passant.map_or(0, |square| square.bitboard());
+        let (single_push, double_push) = match colour {
+            PieceColour::White => (square.bitboard() << 8, square.bitboard() << 16),
+            PieceColour::Black => (square.bitboard() >> 8, square.bitboard() >> 16),
+        };
+        let mut targets = PAWN_ATTACKS[colour as usize][square.index() as usize] & enemies;
+        if single_push & occupied == 0 {
+            targets |= single_push;
+            if SimpleSquare::from(square).is_starting_rank(colour) && double_push & occupied == 0 {
+                targets |= double_push;
+            }
+        }
+        for dest in Squares(targets) {
+            if dest.rank() == 0 || dest.rank() == 7 {
+                for promote_to in [PieceKind::Knight, PieceKind::Queen, PieceKind::Bishop, PieceKind::Rook] {
+                    moves.push(BitMove::new(square, dest, Some(promote_to)));
+                }
+            } else {
+                moves.push(BitMove::new(square, dest, None));
+            }
+        }
+    }
+
+    fn push_castle_moves(&self, square: BitSquare, colour: PieceColour, moves: &mut Vec<BitMove>) {
+        let back_rank = Self::back_rank(colour);
+        if square.rank() != back_rank {
+            return;
+        }
+        let rook = BitPiece::new(PieceKind::Rook, colour);
+        let occupied = self.layout.occupied();
+        for side in [CastlingSide::KingSide, CastlingSide::QueenSide] {
+            let Some(rook_file) = self.castling_rights[castling_index(colour, side)] else {
+                continue;
+            };
+            let (empty, king_path) = castling::path_files(square.file(), rook_file, side);
+            if self.layout.piece_at(BitSquare::new(rook_file, back_rank)) == Some(rook)
+                && occupied & (u64::from(empty) << (back_rank * 8)) == 0
+                && !Squares(u64::from(king_path) << (back_rank * 8))
+                    .any(|square| self.layout.square_attacked(square, !colour))
+            {
+                let dest = castling::king_dest_file(square.file(), rook_file, side);
+                moves.push(BitMove::new(square, BitSquare::new(dest, back_rank), None));
+            }
+        }
+    }
+
+    pub(crate) fn king_in_check(&self, colour: PieceColour) -> Result<bool, ChessError> {
+        Ok(self.layout.square_attacked(self.layout.king_square(colour)?, !colour))
+    }
+
+    /// Squares the side to move can drop a piece of `kind` on in Crazyhouse without leaving its
+    /// king in check, where pawns can't be dropped on the first or last rank
+    pub(crate) fn drop_squares(&self, kind: PieceKind) -> Result<Vec<BitSquare>, ChessError> {
+        let mut empty = !self.layout.occupied();
+        if kind == PieceKind::Pawn {
+            empty &= !BACK_RANKS;
+        }
+        if !self.king_in_check(self.turn)? {
+            return Ok(Squares(empty).collect());
+        }
+        // Only drops blocking the check are legal
+        let piece = BitPiece::new(kind, self.turn);
+        let king = self.layout.king_square(self.turn)?;
+        Ok(Squares(empty)
+            .filter(|&square| {
+                let mut layout = self.layout;
+                layout.toggle_piece(square, piece);
+                !layout.square_attacked(king, !self.turn)
+            })
+            .collect())
+    }
+
+    /// Drop a piece of `kind` for the side to move onto the empty `square` in Crazyhouse, updating
+    /// the rest of the board state as for a move of that piece
+    ///
+    /// The drop is taken back with [`traits::ChessBoard::unmake_move`] like any other move.
+    pub(crate) fn make_drop(&mut self, kind: PieceKind, square: BitSquare) -> BitUndo {
+        let piece = BitPiece::new(kind, self.turn);
+        self.board_history.push(self.hash());
+        let undo = BitUndo {
+            chess_move: UndoMove::Drop(square),
+            piece,
+            taken: None,
+            en_passant: self.en_passant,
+            castling_rights: self.castling_rights,
+            halfmove_clock: self.halfmove_clock,
+        };
+        self.layout.toggle_piece(square, piece);
+        self.halfmove_clock = if kind == PieceKind::Pawn {
+            0
+        } else {
+            self.halfmove_clock + 1
+        };
+        self.en_passant = None;
+        self.turn = !self.turn;
+        if self.turn == PieceColour::White {
+            self.fullmove_number += 1;
+        }
+        undo
+    }
+
+    /// Check if making `chess_move` would leave the king of the moving side in check
+    fn leaves_king_in_check(&self, chess_move: BitMove) -> Result<bool, ChessError> {
+        let mut layout = self.layout;
+        let (piece, _) = layout.apply_move(chess_move, self.en_passant, self.castling_rights)?;
+        Ok(layout.square_attacked(layout.king_square(piece.colour)?, !piece.colour))
+    }
+
+    fn disambiguate_normal(&self, chess_move: AmbiguousMove) -> Result<BitMove, ChessError> {
+        let AmbiguousMove::Normal {
+            piece_kind,
+            src_file,
+            src_rank,
+            takes,
+            dest,
+            promote_to,
+            action,
+        } = chess_move
+        else {
+            panic!("Can't use normal move disambiguator on castle")
+        };
+        let dest = BitSquare::from(dest);
+        let mut matches = vec![];
+        for unambiguous_move in self.all_legal_moves()? {
+            if unambiguous_move.dest() != dest
+                || unambiguous_move.promote_to() != promote_to
+                || self.get_piece(unambiguous_move.src())?.kind() != piece_kind
+                || src_file.is_some_and(|file| unambiguous_move.src().file() != file)
+                || src_rank.is_some_and(|rank| unambiguous_move.src().rank() != rank)
+                || (takes
+                    && self.get_piece(dest).is_err()
+                    && !(piece_kind == PieceKind::Pawn && self.en_passant == Some(dest)))
+            {
+                continue;
+            }
+            if let Some(action) = action {
+                let mut board = self.clone();
+                board.move_piece(unambiguous_move)?;
+                if board.state()? != action.into() {
+                    continue;
+                }
+            }
+            matches.push(unambiguous_move);
+        }
+        match matches.len() {
+            0 => Err(ChessError::ImpossibleMove(chess_move)),
+            1 => Ok(matches[0]),
+            _ => Err(ChessError::AmbiguousMove(chess_move)),
+        }
+    }
+
+    fn disambiguate_castling(&self, side: CastlingSide) -> BitMove {
+        let rank = Self::back_rank(self.turn);
+        // Without the right to castle this gives the standard castling move, which isn't legal
+        let (king_file, dest) = match self.castling_rights[castling_index(self.turn, side)] {
+            Some(rook_file) => {
+                let king_file = self.layout.king_square(self.turn).map_or(4, |king| king.file());
+                (king_file, castling::king_dest_file(king_file, rook_file, side))
+            }
+            None => (4, castling::final_files(side).0),
+        };
+        BitMove::new(BitSquare::new(king_file, rank), BitSquare::new(dest, rank), None)
+    }
+
+    fn fmt_board(&self) -> String {
+        let mut outstr = String::with_capacity(172);
+        for i in (0..8).rev() {
+            outstr.push(notation::rank_to_char(i).unwrap());
+            for j in 0..8 {
+                outstr.push(' ');
+                if let Some(piece) = self.layout.piece_at(BitSquare::new(j, i)) {
+                    outstr.push(piece.as_fen());
+                } else if (i + j) % 2 == 1 {
+                    outstr.push('◼');
+                } else {
+                    outstr.push(' ');
+                }
+            }
+            outstr.push('\n');
+        }
+
+        outstr.push_str("  ");
+        for j in 0..8 {
+            outstr.push(notation::file_to_char(j).unwrap());
+            outstr.push(' ');
+        }
+        outstr
+    }
+}
+
+impl fmt::Display for BitBoard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.fmt_board())
+    }
+}
+
+impl From<&BitBoard> for Fen {
+    fn from(value: &BitBoard) -> Self {
+        let mut layout: Box<[[Option<SimplePiece>; 8]; 8]> = Box::new([[None; 8]; 8]);
+        for (inverse_rank_number, rank) in layout.iter_mut().enumerate() {
+            for (file_number, piece) in rank.iter_mut().enumerate() {
+                *piece = value
+                    .layout
+                    .piece_at(BitSquare::new(file_number as u8, 7 - inverse_rank_number as u8))
+                    .map(SimplePiece::from);
+            }
+        }
+        Self {
+            layout,
+            pockets: None,
+            promoted: 0,
+            checks: None,
+            turn: value.turn,
+            castling_rights: value.castling_rights,
+            en_passant: value.en_passant.map(SimpleSquare::from),
+            halfmove_clock: value.halfmove_clock,
+            fullmove_number: value.fullmove_number,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enums::{DrawReason, GameOutcome};
+    use proptest::proptest;
+
+    fn moves_from_strs(moves: Vec<&str>) -> Vec<BitMove> {
+        let mut new_moves: Vec<BitMove> = moves.into_iter().map(|s| BitMove::from_pgn_str(s).unwrap()).collect();
+        new_moves.sort();
+        new_moves
+    }
+
+    fn piece_moves(board: &BitBoard, square: &str) -> Vec<BitMove> {
+        let mut moves: Vec<BitMove> = board
+            .piece_plegal_moves(BitSquare::from_pgn_str(square).unwrap())
+            .unwrap()
+            .into_iter()
+            .collect();
+        moves.sort();
+        moves
+    }
+
+    proptest! {
+        #[test]
+        fn simple_move_round_trip(chess_move in SimpleMove::strategy()) {
+            assert_eq!(SimpleMove::from(BitMove::from(chess_move)), chess_move);
+        }
+
+        #[test]
+        fn simple_square_round_trip(square in SimpleSquare::strategy()) {
+            assert_eq!(SimpleSquare::from(BitSquare::from(square)), square);
+        }
+    }
+
+    #[test]
+    fn square_index() {
+        let square = BitSquare::from_pgn_str("c2").unwrap();
+        assert_eq!(square.index(), 10);
+        assert_eq!(square.bitboard(), 1 << 10);
+        assert_eq!((square.file(), square.rank()), (2, 1));
+    }
+
+    #[test]
+    #[should_panic(expected = "Rank must be between 0-7 inclusive, 8 > 7")]
+    fn wrong_range_square() {
+        let _ = BitSquare::new(3, 8);
+    }
+
+    #[test]
+    fn fen_round_trip() {
+        let fen = "r1bqk2r/ppp1bppp/2np1n2/4p3/4P3/2NPB3/PPP1QPPP/R3KBNR w KQkq - 3 7";
+        assert_eq!(BitBoard::from_fen(fen).unwrap().to_fen().to_string(), fen);
+    }
+
+    #[test]
+    fn starting_moves() {
+        let board = BitBoard::starting_board();
+        assert_eq!(board.all_legal_moves().unwrap().into_iter().count(), 20);
+    }
+
+    #[test]
+    fn pawn_double_push_blocked() {
+        let board = BitBoard::from_fen("rnbqkbnr/pppppppp/8/2P5/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1").unwrap();
+        assert_eq!(piece_moves(&board, "c7"), moves_from_strs(vec!["c7c6"]));
+    }
+
+    #[test]
+    fn en_passant() {
+        let mut board = BitBoard::from_fen("rnbqkbnr/pppppppp/8/5P2/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 2").unwrap();
+        board.move_piece(BitMove::from_pgn_str("g7g5").unwrap()).unwrap();
+        assert_eq!(piece_moves(&board, "f5"), moves_from_strs(vec!["f5f6", "f5g6"]));
+        board.move_piece(BitMove::from_pgn_str("f5g6").unwrap()).unwrap();
+        board.get_piece(BitSquare::from_pgn_str("g5").unwrap()).unwrap_err();
+    }
+
+    #[test]
+    fn promotions() {
+        let board = BitBoard::from_fen("1n5k/P7/8/8/8/8/8/K7 w - - 0 1").unwrap();
+        assert_eq!(
+            piece_moves(&board, "a7"),
+            moves_from_strs(vec![
+                "a7a8=N", "a7a8=B", "a7a8=R", "a7a8=Q", "a7b8=N", "a7b8=B", "a7b8=R", "a7b8=Q"
+            ])
+        );
+    }
+
+    #[test]
+    fn queen_moves() {
+        let board = BitBoard::from_fen("rnbqkbnr/pppppppp/8/5Q2/8/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2").unwrap();
+        let exp_moves = moves_from_strs(vec![
+            "f5f6", "f5f7", "f5f4", "f5f3", "f5g5", "f5h5", "f5e5", "f5d5", "f5c5", "f5b5", "f5a5", "f5g6", "f5h7",
+            "f5g4", "f5h3", "f5e6", "f5d7", "f5e4", "f5d3",
+        ]);
+        assert_eq!(piece_moves(&board, "f5"), exp_moves);
+    }
+
+    #[test]
+    fn pinned_piece() {
+        let board = BitBoard::from_fen("k3r3/8/4N3/8/4K3/8/8/8 w - - 0 2").unwrap();
+        assert!(
+            board
+                .piece_legal_moves(BitSquare::from_pgn_str("e6").unwrap())
+                .unwrap()
+                .into_iter()
+                .next()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn illegal_castle() {
+        let board = BitBoard::from_fen("rn1qkbnr/ppp2ppp/3p4/1b2N3/4P3/8/PPPP1PPP/RNBQK2R w KQkq - 0 1").unwrap();
+        assert!(!board.is_move_legal(BitMove::from_pgn_str("e1g1").unwrap()).unwrap());
+    }
+
+    #[test]
+    fn queenside_castle() {
+        let mut board =
+            BitBoard::from_fen("r1bqk2r/ppp1bppp/2np1n2/4p3/4P3/2NPB3/PPP1QPPP/R3KBNR w KQkq - 0 1").unwrap();
+        board.move_piece_legal(BitMove::from_pgn_str("e1c1").unwrap()).unwrap();
+        assert_eq!(
+            board.to_fen().to_string(),
+            "r1bqk2r/ppp1bppp/2np1n2/4p3/4P3/2NPB3/PPP1QPPP/2KR1BNR b kq - 1 1"
+        );
+    }
+
+    #[test]
+    fn castling_invalidation_rook_taken() {
+        let mut board = BitBoard::from_fen("r3k3/8/8/8/8/8/8/4K2R w Kq - 0 1").unwrap();
+        board.move_piece(BitMove::from_pgn_str("h1h8").unwrap()).unwrap();
+        board.move_piece(BitMove::from_pgn_str("e8e7").unwrap()).unwrap();
+        assert_eq!(board.castling_rights, [None, None, None, None]);
+    }
+
+    #[test]
+    fn unmake_castle() {
+        let mut board = BitBoard::from_fen("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 3 10").unwrap();
+        let original = board.clone();
+        let undo = board.make_move(BitMove::from_pgn_str("e8c8").unwrap()).unwrap();
+        assert_eq!(board.to_fen().to_string(), "2kr3r/8/8/8/8/8/8/R3K2R w KQ - 4 11");
+        board.unmake_move(undo);
+        assert_eq!(board, original);
+    }
+
+    #[test]
+    fn checkmate() {
+        let board = BitBoard::from_fen("rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3").unwrap();
+        assert_eq!(board.state().unwrap(), BoardState::Checkmate);
+    }
+
+    #[test]
+    fn threefold_repetition() {
+        let mut board = BitBoard::starting_board();
+        for _ in 0..2 {
+            board.move_piece(BitMove::from_pgn_str("g1f3").unwrap()).unwrap();
+            board.move_piece(BitMove::from_pgn_str("g8f6").unwrap()).unwrap();
+            board.move_piece(BitMove::from_pgn_str("f3g1").unwrap()).unwrap();
+            board.move_piece(BitMove::from_pgn_str("f6g8").unwrap()).unwrap();
+        }
+        assert_eq!(board.state().unwrap(), BoardState::Normal);
+        assert_eq!(
+            board.outcome().unwrap(),
+            GameOutcome::ClaimableDraw(DrawReason::ThreefoldRepetition)
+        );
+    }
+
+    #[test]
+    fn insufficient_material() {
+        assert!(
+            BitBoard::from_fen("4kb2/8/8/8/8/8/8/2B1K3 w - - 0 1")
+                .unwrap()
+                .insufficient_material()
+        );
+        assert!(
+            BitBoard::from_fen("4k3/8/8/8/8/8/8/4KN2 w - - 0 1")
+                .unwrap()
+                .insufficient_material()
+        );
+        assert!(
+            !BitBoard::from_fen("4k3/8/8/8/8/8/8/2B1KB2 w - - 0 1")
+                .unwrap()
+                .insufficient_material()
+        );
+        assert!(
+            !BitBoard::from_fen("4kn2/8/8/8/8/8/8/4KB2 w - - 0 1")
+                .unwrap()
+                .insufficient_material()
+        );
+    }
+}
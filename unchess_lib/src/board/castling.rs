@@ -0,0 +1,159 @@
+//! Castling rules shared by every board representation, covering standard chess and Chess960
+//!
+//! Castling rights are kept as the file of the rook each one castles with, in the order white
+//! kingside, white queenside, black kingside, black queenside. Wherever the king and rook start,
+//! the king ends on the g or c-file and the rook on the f or d-file. Every square between the king
+//! and its destination and between the rook and its destination must be empty apart from the king
+//! and rook themselves, and the king can't start on, pass through or land on an attacked square.
+//!
+//! When the king starts on the e-file and the rook on the h or a-file, as in standard chess,
+//! castling is written as the king moving two squares. Otherwise it is written as the king taking
+//! its own rook, since in Chess960 the king may move one square or not move at all.
+
+use crate::enums::{CastlingSide, PieceColour, PieceKind};
+use crate::notation::fen::castling_index;
+use crate::traits::ChessSquare;
+
+/// Files the king and rook end on after castling on `side`
+pub const fn final_files(side: CastlingSide) -> (u8, u8) {
+    match side {
+        CastlingSide::KingSide => (6, 5),
+        CastlingSide::QueenSide => (2, 3),
+    }
+}
+
+/// File the king moves to in the move for castling with the rook on `rook_file`
+pub fn king_dest_file(king_file: u8, rook_file: u8, side: CastlingSide) -> u8 {
+    let standard_rook_file = match side {
+        CastlingSide::KingSide => 7,
+        CastlingSide::QueenSide => 0,
+    };
+    if king_file == 4 && rook_file == standard_rook_file {
+        final_files(side).0
+    } else {
+        rook_file
+    }
+}
+
+/// Files that must be empty apart from the king and rook, and files the king must not be attacked
+/// on, as masks with bit n set for file n
+pub fn path_files(king_file: u8, rook_file: u8, side: CastlingSide) -> (u8, u8) {
+    let span = |a: u8, b: u8| (a.min(b)..=a.max(b)).fold(0u8, |mask, file| mask | (1 << file));
+    let (king_final, rook_final) = final_files(side);
+    let king_path = span(king_file, king_final);
+    let empty = (king_path | span(rook_file, rook_final)) & !(1 << king_file) & !(1 << rook_file);
+    (empty, king_path)
+}
+
+/// Side and rook file if a king of `colour` moving from `src` to `dest` is castling, given the
+/// castling rights before the move
+pub fn castle(
+    castling_rights: [Option<u8>; 4],
+    colour: PieceColour,
+    src: &impl ChessSquare,
+    dest: &impl ChessSquare,
+) -> Option<(CastlingSide, u8)> {
+    if src.rank() != dest.rank() {
+        return None;
+    }
+    [CastlingSide::KingSide, CastlingSide::QueenSide]
+        .into_iter()
+        .find_map(|side| {
+            let rook_file = castling_rights[castling_index(colour, side)]?;
+            (dest.file() == king_dest_file(src.file(), rook_file, side)).then_some((side, rook_file))
+        })
+}
+
+/// Revoke the castling rights lost by a piece of `kind` and `colour` moving from `src` to `dest`
+///
+/// A king moving loses both of its rights, and a rook leaving or being taken on its starting
+/// square loses the right to castle with it.
+pub fn revoke(
+    castling_rights: &mut [Option<u8>; 4],
+    kind: PieceKind,
+    colour: PieceColour,
+    src: &impl ChessSquare,
+    dest: &impl ChessSquare,
+) {
+    if kind == PieceKind::King {
+        castling_rights[castling_index(colour, CastlingSide::KingSide)] = None;
+        castling_rights[castling_index(colour, CastlingSide::QueenSide)] = None;
+    }
+    for (colour, back_rank) in [(PieceColour::White, 0), (PieceColour::Black, 7)] {
+        for side in [CastlingSide::KingSide, CastlingSide::QueenSide] {
+            let right = &mut castling_rights[castling_index(colour, side)];
+            if right.is_some_and(|file| {
+                (src.file(), src.rank()) == (file, back_rank) || (dest.file(), dest.rank()) == (file, back_rank)
+            }) {
+                *right = None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simple_types::SimpleSquare;
+
+    fn square(s: &str) -> SimpleSquare {
+        SimpleSquare::from_pgn_str(s).unwrap()
+    }
+
+    #[test]
+    fn paths() {
+        assert_eq!(path_files(4, 7, CastlingSide::KingSide), (0b0110_0000, 0b0111_0000));
+        assert_eq!(path_files(4, 0, CastlingSide::QueenSide), (0b0000_1110, 0b0001_1100));
+        assert_eq!(path_files(1, 0, CastlingSide::QueenSide), (0b0000_1100, 0b0000_0110));
+        assert_eq!(path_files(6, 7, CastlingSide::KingSide), (0b0010_0000, 0b0100_0000));
+    }
+
+    #[test]
+    fn castles() {
+        let rights = [Some(7), Some(0), Some(6), Some(1)];
+        assert_eq!(
+            castle(rights, PieceColour::White, &square("e1"), &square("g1")),
+            Some((CastlingSide::KingSide, 7))
+        );
+        assert_eq!(castle(rights, PieceColour::White, &square("e1"), &square("h1")), None);
+        assert_eq!(
+            castle(rights, PieceColour::Black, &square("e8"), &square("g8")),
+            Some((CastlingSide::KingSide, 6))
+        );
+        assert_eq!(
+            castle(rights, PieceColour::Black, &square("e8"), &square("b8")),
+            Some((CastlingSide::QueenSide, 1))
+        );
+        assert_eq!(castle(rights, PieceColour::Black, &square("e8"), &square("c8")), None);
+        assert_eq!(castle(rights, PieceColour::White, &square("e1"), &square("f2")), None);
+    }
+
+    #[test]
+    fn revoked() {
+        let mut rights = [Some(7), Some(1), Some(7), Some(1)];
+        revoke(
+            &mut rights,
+            PieceKind::Rook,
+            PieceColour::White,
+            &square("b1"),
+            &square("b5"),
+        );
+        assert_eq!(rights, [Some(7), None, Some(7), Some(1)]);
+        revoke(
+            &mut rights,
+            PieceKind::Bishop,
+            PieceColour::White,
+            &square("c6"),
+            &square("h8"),
+        );
+        assert_eq!(rights, [Some(7), None, None, Some(1)]);
+        revoke(
+            &mut rights,
+            PieceKind::King,
+            PieceColour::Black,
+            &square("e8"),
+            &square("d7"),
+        );
+        assert_eq!(rights, [Some(7), None, None, None]);
+    }
+}
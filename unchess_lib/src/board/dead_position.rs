@@ -0,0 +1,129 @@
+//! Detection of positions where neither side can ever checkmate
+//!
+//! Covers the common case of the pawns being locked together with only the kings left to move. If
+//! no pawn can ever push or take and neither king can reach an undefended enemy pawn, the pawns
+//! never move again and no check can ever be given, so the game can't end in checkmate.
+//!
+//! Works on bitboards so it can be shared by every board representation, bit 0 representing a1 and
+//! bit 63 representing h8, with arrays indexed by [`crate::enums::PieceColour`].
+
+use crate::enums::PieceColour;
+
+const FILE_A: u64 = 0x0101_0101_0101_0101;
+const FILE_H: u64 = 0x8080_8080_8080_8080;
+
+/// Squares attacked by `pawns` of colour `colour`
+fn pawn_attacks(pawns: u64, colour: PieceColour) -> u64 {
+    match colour {
+        PieceColour::White => ((pawns << 7) & !FILE_H) | ((pawns << 9) & !FILE_A),
+        PieceColour::Black => ((pawns >> 9) & !FILE_H) | ((pawns >> 7) & !FILE_A),
+    }
+}
+
+/// `squares` along with every square a king could step to from them
+fn king_step(squares: u64) -> u64 {
+    let rank = squares | ((squares << 1) & !FILE_A) | ((squares >> 1) & !FILE_H);
+    rank | (rank << 8) | (rank >> 8)
+}
+
+/// Whether the pawns are locked so that no checkmate is possible
+///
+/// True if there are only kings and pawns on the board, every pawn is blocked by another pawn and
+/// can't take anything, and neither king can walk to an enemy pawn that isn't defended by another
+/// pawn. Only kings and pawns should be given, `others` being every other piece on the board.
+pub(crate) fn pawns_locked(pawns: [u64; 2], kings: [u64; 2], others: u64) -> bool {
+    let all_pawns = pawns[0] | pawns[1];
+    let white = pawns[PieceColour::White as usize];
+    let black = pawns[PieceColour::Black as usize];
+    if others != 0
+        || (white << 8) & !all_pawns != 0
+        || (black >> 8) & !all_pawns != 0
+        || pawn_attacks(white, PieceColour::White) & black != 0
+        || pawn_attacks(black, PieceColour::Black) & white != 0
+    {
+        return false;
+    }
+    for colour in [PieceColour::White, PieceColour::Black] {
+        let enemy_pawns = pawns[!colour as usize];
+        let walkable = !pawns[colour as usize] & !pawn_attacks(enemy_pawns, !colour);
+        let mut reachable = kings[colour as usize];
+        loop {
+            let next = reachable | (king_step(reachable) & walkable);
+            if next & enemy_pawns != 0 {
+                return false;
+            }
+            if next == reachable {
+                break;
+            }
+            reachable = next;
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::bitboard::BitBoard;
+    use crate::board::piece_list::PieceListBoard;
+    use crate::enums::{DrawReason, GameOutcome};
+    use crate::traits::{ChessBoard as _, LegalMoveGenerator as _};
+
+    fn is_dead(fen: &str) -> bool {
+        let dead = BitBoard::from_fen(fen).unwrap().is_dead_position();
+        assert_eq!(PieceListBoard::from_fen(fen).unwrap().is_dead_position(), dead, "{fen}");
+        dead
+    }
+
+    #[test]
+    fn locked_pawns() {
+        let fen = "8/8/4k3/1p1p1p1p/1P1P1P1P/4K3/8/8 w - - 0 1";
+        assert!(is_dead(fen));
+        assert_eq!(
+            BitBoard::from_fen(fen).unwrap().outcome().unwrap(),
+            GameOutcome::Draw(DrawReason::DeadPosition)
+        );
+    }
+
+    #[test]
+    fn king_reaches_undefended_pawn() {
+        assert!(!is_dead("8/8/4k3/1p1p1p2/1P1P1P2/4K3/8/8 w - - 0 1"));
+    }
+
+    #[test]
+    fn pawn_can_take() {
+        assert!(!is_dead("8/8/4k3/1p1p1pp1/1P1P1P1P/4K3/8/8 w - - 0 1"));
+    }
+
+    #[test]
+    fn pawn_can_push() {
+        assert!(!is_dead("8/8/4k3/1p1p1p2/1P1P1P2/4K2P/8/8 w - - 0 1"));
+    }
+
+    #[test]
+    fn other_pieces() {
+        assert!(!is_dead("8/8/4k3/1p1p1p1p/1P1P1P1P/4K3/8/7R w - - 0 1"));
+    }
+
+    #[test]
+    fn insufficient_material_is_dead() {
+        assert!(is_dead("4k3/8/8/8/8/8/8/4KN2 w - - 0 1"));
+    }
+
+    #[test]
+    fn pawn_attacks_wrap() {
+        // a2 and h2
+        assert_eq!(pawn_attacks(0x8100, PieceColour::White), 0x42_0000);
+        // a7 and h7
+        assert_eq!(
+            pawn_attacks(0x0081_0000_0000_0000, PieceColour::Black),
+            0x0000_4200_0000_0000
+        );
+    }
+
+    #[test]
+    fn king_step_corner() {
+        assert_eq!(king_step(1), 0x303);
+        assert_eq!(king_step(1 << 63), 0xc0c0_0000_0000_0000);
+    }
+}
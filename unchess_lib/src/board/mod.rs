@@ -1,4 +1,7 @@
 //! Chess board representations
 
 pub mod bitboard;
+mod castling;
+mod dead_position;
 pub mod piece_list;
+pub mod zobrist;
@@ -7,9 +7,10 @@
 use core::fmt;
 use std::ops::{Add, AddAssign, Div, Mul, Sub};
 
+use crate::board::{castling, dead_position, zobrist};
 use crate::enums::{AmbiguousMove, BoardState, CastlingSide, PieceColour, PieceKind};
-use crate::error::ChessError;
-use crate::parser::fen::Fen;
+use crate::error::{ChessError, PositionError};
+use crate::notation::fen::{Fen, castling_index};
 use crate::simple_types::{SimpleMove, SimplePiece, SimpleSquare};
 use crate::traits::{
     ChessBoard as _, ChessMove as _, ChessPiece as _, ChessSquare as _, LegalMoveGenerator, PLegalMoveGenerator,
@@ -166,7 +167,17 @@ impl PieceWithPos {
     }
 }
 
-type BoardHistoryElem = (Vec<PieceWithPos>, PieceColour, Option<SimpleSquare>, [bool; 4]);
+/// Information needed to take back a move on a [`PieceListBoard`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PieceListUndo {
+    chess_move: SimpleMove,
+    kind: PieceKind,
+    taken: Option<(usize, PieceWithPos)>,
+    en_passant: Option<SimpleSquare>,
+    castling_rights: [Option<u8>; 4],
+    halfmove_clock: u32,
+    hash: u64,
+}
 
 /// Piece list representation of chess board
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
@@ -174,16 +185,24 @@ pub struct PieceListBoard {
     pieces: Vec<PieceWithPos>,
     turn: PieceColour,
     en_passant: Option<SimpleSquare>,
-    castling_rights: [bool; 4],
+    castling_rights: [Option<u8>; 4],
     halfmove_clock: u32,
     fullmove_number: u32,
-    board_history: Vec<BoardHistoryElem>,
+    /// Hashes of every earlier position, oldest first
+    board_history: Vec<u64>,
+    hash: u64,
 }
 
 impl traits::ChessBoard for PieceListBoard {
     type Square = SimpleSquare;
     type Piece = PieceWithPos;
     type Move = SimpleMove;
+    type Undo = PieceListUndo;
+
+    fn to_fen(&self) -> Fen {
+        Fen::from(self)
+    }
+
     fn get_piece(&self, square: SimpleSquare) -> Result<PieceWithPos, ChessError> {
         let pieces = self.pieces.iter().filter(|&&piece| piece.square() == square);
         match pieces.at_most_one() {
@@ -197,27 +216,98 @@ impl traits::ChessBoard for PieceListBoard {
         self.pieces.iter().copied().sorted_unstable()
     }
 
-    fn move_piece(&mut self, chess_move: SimpleMove) -> Result<(), ChessError> {
+    fn turn(&self) -> PieceColour {
+        self.turn
+    }
+
+    fn halfmove_clock(&self) -> u32 {
+        self.halfmove_clock
+    }
+
+    fn repetition_count(&self) -> usize {
+        zobrist::repetitions(&self.board_history, self.hash, self.halfmove_clock) + 1
+    }
+
+    fn insufficient_material(&self) -> bool {
+        let pieces: Vec<&PieceWithPos> = self
+            .pieces
+            .iter()
+            .filter(|piece| piece.kind != PieceKind::King)
+            .collect();
+        match pieces.as_slice() {
+            [] => true,
+            [piece] => matches!(piece.kind, PieceKind::Bishop | PieceKind::Knight),
+            _ => {
+                pieces.iter().all(|piece| piece.kind == PieceKind::Bishop)
+                    && pieces
+                        .iter()
+                        .map(|piece| (piece.square.file() + piece.square.rank()) % 2)
+                        .all_equal()
+            }
+        }
+    }
+
+    fn is_dead_position(&self) -> bool {
+        let mut pawns = [0; 2];
+        let mut kings = [0; 2];
+        let mut others = 0;
+        for piece in &self.pieces {
+            let bit = 1 << (piece.square.rank() * 8 + piece.square.file());
+            match piece.kind {
+                PieceKind::Pawn => pawns[piece.colour as usize] |= bit,
+                PieceKind::King => kings[piece.colour as usize] |= bit,
+                _ => others |= bit,
+            }
+        }
+        self.insufficient_material() || (self.en_passant.is_none() && dead_position::pawns_locked(pawns, kings, others))
+    }
+
+    fn hash(&self) -> u64 {
+        self.hash
+    }
+
+    fn make_move(&mut self, chess_move: SimpleMove) -> Result<PieceListUndo, ChessError> {
         const PAWN_DOUBLE_PUSH: i8 = 2;
+        let moving = self.get_piece(chess_move.src())?;
+        if moving.kind == PieceKind::King
+            && let Some(castle) = castling::castle(
+                self.castling_rights,
+                moving.colour,
+                &chess_move.src(),
+                &chess_move.dest(),
+            )
+        {
+            return self.make_castle(chess_move, moving, castle);
+        }
         let taken_piece = self.pieces.iter().position(|piece| piece.square() == chess_move.dest());
+        let mut undo = PieceListUndo {
+            chess_move,
+            kind: moving.kind,
+            taken: None,
+            en_passant: self.en_passant,
+            castling_rights: self.castling_rights,
+            halfmove_clock: self.halfmove_clock,
+            hash: self.hash,
+        };
 
+        self.hash ^= zobrist::state_key(self.turn, self.castling_rights, self.en_passant);
         self.halfmove_clock += 1;
-        self.board_history.push((
-            self.all_pieces().into_iter().collect(),
-            self.turn,
-            self.en_passant,
-            self.castling_rights,
-        ));
+        self.board_history.push(undo.hash);
 
         let piece = self.get_piece_mut(chess_move.src())?;
+        let mut piece_hash = zobrist::piece_key(piece, &piece.square());
         piece.move_piece(chess_move.dest());
         if let Some(promote_to) = chess_move.promote_to() {
             piece.kind = promote_to;
         }
+        piece_hash ^= zobrist::piece_key(piece, &piece.square());
         let piece = piece.to_owned();
+        self.hash ^= piece_hash;
         // Wait till after moving piece succeeds to take
         if let Some(taken_index) = taken_piece {
-            self.pieces.remove(taken_index);
+            let taken = self.pieces.remove(taken_index);
+            self.hash ^= zobrist::piece_key(&taken, &taken.square());
+            undo.taken = Some((taken_index, taken));
             self.halfmove_clock = 0;
         }
 
@@ -226,22 +316,60 @@ impl traits::ChessBoard for PieceListBoard {
         }
 
         let offset = chess_move.dest() - chess_move.src();
-        self.castle_rook(piece, offset)?;
-
-        self.take_en_passant(piece, offset)?;
+        if let Some((index, taken_pawn)) = self.take_en_passant(piece, offset)? {
+            self.hash ^= zobrist::piece_key(&taken_pawn, &taken_pawn.square());
+            undo.taken = Some((index, taken_pawn));
+        }
 
         if piece.kind() == PieceKind::Pawn && offset.rank.abs() == PAWN_DOUBLE_PUSH {
             self.en_passant = Some(chess_move.src() + offset / 2);
         } else {
             self.en_passant = None;
         }
-        self.update_castling_rights(piece, chess_move);
+        castling::revoke(
+            &mut self.castling_rights,
+            piece.kind,
+            piece.colour,
+            &chess_move.src(),
+            &chess_move.dest(),
+        );
 
-        self.turn = !self.turn;
+        self.end_turn();
+        Ok(undo)
+    }
+
+    fn unmake_move(&mut self, undo: PieceListUndo) {
         if self.turn == PieceColour::White {
-            self.fullmove_number += 1;
+            self.fullmove_number -= 1;
+        }
+        self.turn = !self.turn;
+        self.board_history.pop();
+
+        let src = undo.chess_move.src();
+        let dest = undo.chess_move.dest();
+        let castle = castling::castle(undo.castling_rights, self.turn, &src, &dest);
+        if let (PieceKind::King, Some((side, rook_file))) = (undo.kind, castle) {
+            let (king_file, rook_final) = castling::final_files(side);
+            let king = self.piece_index(SimpleSquare::new(king_file, src.rank()));
+            let rook = self.piece_index(SimpleSquare::new(rook_final, src.rank()));
+            if let (Some(king), Some(rook)) = (king, rook) {
+                self.pieces[king].move_piece(src);
+                self.pieces[rook].move_piece(SimpleSquare::new(rook_file, src.rank()));
+            }
+        } else {
+            if let Ok(piece) = self.get_piece_mut(dest) {
+                piece.move_piece(src);
+                piece.kind = undo.kind;
+            }
         }
-        Ok(())
+        if let Some((index, taken)) = undo.taken {
+            self.pieces.insert(index, taken);
+        }
+
+        self.en_passant = undo.en_passant;
+        self.castling_rights = undo.castling_rights;
+        self.halfmove_clock = undo.halfmove_clock;
+        self.hash = undo.hash;
     }
 }
 
@@ -283,29 +411,13 @@ impl PLegalMoveGenerator for PieceListBoard {
 
     fn piece_plegal_moves(&self, square: SimpleSquare) -> Result<impl IntoIterator<Item = SimpleMove>, ChessError> {
         let piece = self.get_piece(square)?;
-        if piece.colour != self.turn
-            || self.halfmove_clock >= 50
-            || self
-                .board_history
-                .iter()
-                .filter(|board_state| {
-                    board_state
-                        == &&(
-                            self.all_pieces().into_iter().collect(),
-                            self.turn,
-                            self.en_passant,
-                            self.castling_rights,
-                        )
-                })
-                .count()
-                >= 2
-        {
+        if piece.colour != self.turn {
             return Ok(vec![]);
         }
         match piece.kind() {
             PieceKind::King => {
                 let mut moves = self.offset_moves(piece.square, piece.colour, &KING_PATTERN)?;
-                moves.append(&mut self.castle_moves(piece.colour)?);
+                moves.append(&mut self.castle_moves(piece.square, piece.colour)?);
                 Ok(moves)
             }
             PieceKind::Queen => self.traversal_moves(piece.square, piece.colour, &QUEEN_DIRECTIONS),
@@ -335,38 +447,16 @@ impl PLegalMoveGenerator for PieceListBoard {
 
 impl LegalMoveGenerator for PieceListBoard {
     fn all_legal_moves(&self) -> Result<impl IntoIterator<Item = SimpleMove>, ChessError> {
-        let mut moves: Vec<SimpleMove> = vec![];
-        for chess_move in self.all_plegal_moves()? {
-            let mut board = self.clone();
-            board.move_piece(chess_move)?;
-            if !board.king_in_check(self.turn)? {
-                moves.push(chess_move);
-            }
-        }
-        Ok(moves)
+        self.legal_subset(self.all_plegal_moves()?)
     }
 
     fn piece_legal_moves(&self, square: SimpleSquare) -> Result<impl IntoIterator<Item = SimpleMove>, ChessError> {
-        let mut moves: Vec<SimpleMove> = vec![];
-        for chess_move in self.piece_plegal_moves(square)? {
-            let mut board = self.clone();
-            board.move_piece(chess_move)?;
-            if !board.king_in_check(self.turn)? {
-                moves.push(chess_move);
-            }
-        }
-        Ok(moves)
+        self.legal_subset(self.piece_plegal_moves(square)?)
     }
 
     fn is_move_legal(&self, chess_move: SimpleMove) -> Result<bool, ChessError> {
         if self.is_move_plegal(chess_move)? {
-            let mut board = self.clone();
-            board.move_piece(chess_move)?;
-            if !board.king_in_check(self.turn)? {
-                Ok(true)
-            } else {
-                Ok(false)
-            }
+            Ok(!self.legal_subset([chess_move])?.is_empty())
         } else {
             Ok(false)
         }
@@ -393,10 +483,16 @@ impl LegalMoveGenerator for PieceListBoard {
         }
     }
 
+    fn in_check(&self) -> Result<bool, ChessError> {
+        self.king_in_check(self.turn)
+    }
+
     fn disambiguate_move(&self, chess_move: AmbiguousMove) -> Result<SimpleMove, ChessError> {
         match chess_move {
             AmbiguousMove::Normal { .. } => self.disambiguate_normal(chess_move),
             AmbiguousMove::Castle { .. } => Ok(self.disambiguate_castling(chess_move)),
+            // Pieces can only be dropped in Crazyhouse
+            AmbiguousMove::Drop { .. } => Err(ChessError::ImpossibleMove(chess_move)),
         }
     }
 }
@@ -424,6 +520,7 @@ impl From<Fen> for PieceListBoard {
             halfmove_clock: value.halfmove_clock,
             fullmove_number: value.fullmove_number,
             board_history: Vec::with_capacity(100),
+            hash: zobrist::fen_hash(&value),
         }
     }
 }
@@ -443,35 +540,87 @@ impl PieceListBoard {
         }
     }
 
-    /// Check if king move was a castle and if so move rook
-    fn castle_rook(&mut self, piece: PieceWithPos, offset: SquareOffset) -> Result<(), ChessError> {
-        const KINGSIDE_CASTLE: i8 = 2;
-        const QUEENSIDE_CASTLE: i8 = -2;
-        if piece.kind() == PieceKind::King && offset.file == KINGSIDE_CASTLE {
-            let rook = self.get_piece_mut(piece.square() + SquareOffset::new(1, 0))?;
-            rook.move_piece(piece.square() + SquareOffset::new(-1, 0));
+    /// Index in the piece list of the piece on `square`, if there is one
+    fn piece_index(&self, square: SimpleSquare) -> Option<usize> {
+        self.pieces.iter().position(|piece| piece.square() == square)
+    }
+
+    /// Make the castling move `chess_move` by `king`, with the side and rook file it castles with
+    fn make_castle(
+        &mut self,
+        chess_move: SimpleMove,
+        king: PieceWithPos,
+        (side, rook_file): (CastlingSide, u8),
+    ) -> Result<PieceListUndo, ChessError> {
+        let rank = chess_move.src().rank();
+        let rook_square = SimpleSquare::new(rook_file, rank);
+        let (king_file, rook_final) = castling::final_files(side);
+        let king_index = self
+            .piece_index(chess_move.src())
+            .ok_or(ChessError::PieceNotFound(chess_move.src()))?;
+        let rook_index = self
+            .piece_index(rook_square)
+            .filter(|&index| self.pieces[index].kind == PieceKind::Rook)
+            .ok_or(ChessError::PieceNotFound(rook_square))?;
+        let undo = PieceListUndo {
+            chess_move,
+            kind: king.kind,
+            taken: None,
+            en_passant: self.en_passant,
+            castling_rights: self.castling_rights,
+            halfmove_clock: self.halfmove_clock,
+            hash: self.hash,
+        };
+        self.hash ^= zobrist::state_key(self.turn, self.castling_rights, self.en_passant);
+        self.halfmove_clock += 1;
+        self.board_history.push(undo.hash);
+
+        for (index, dest) in [
+            (king_index, SimpleSquare::new(king_file, rank)),
+            (rook_index, SimpleSquare::new(rook_final, rank)),
+        ] {
+            let piece = &mut self.pieces[index];
+            let mut piece_hash = zobrist::piece_key(piece, &piece.square());
+            piece.move_piece(dest);
+            piece_hash ^= zobrist::piece_key(piece, &piece.square());
+            self.hash ^= piece_hash;
         }
-        if piece.kind() == PieceKind::King && offset.file == QUEENSIDE_CASTLE {
-            let rook = self.get_piece_mut(piece.square() + SquareOffset::new(-2, 0))?;
-            rook.move_piece(piece.square() + SquareOffset::new(1, 0));
+        self.en_passant = None;
+        castling::revoke(
+            &mut self.castling_rights,
+            king.kind,
+            king.colour,
+            &chess_move.src(),
+            &chess_move.dest(),
+        );
+        self.end_turn();
+        Ok(undo)
+    }
+
+    /// Pass the turn to the other side after a move, hashing the new state
+    fn end_turn(&mut self) {
+        self.turn = !self.turn;
+        if self.turn == PieceColour::White {
+            self.fullmove_number += 1;
         }
-        Ok(())
+        self.hash ^= zobrist::state_key(self.turn, self.castling_rights, self.en_passant);
     }
 
     /// Check if move was en passant and if so take other pawn
-    fn take_en_passant(&mut self, piece: PieceWithPos, offset: SquareOffset) -> Result<(), ChessError> {
+    ///
+    /// Returns the index the taken pawn was at in the piece list along with the pawn.
+    fn take_en_passant(
+        &mut self,
+        piece: PieceWithPos,
+        offset: SquareOffset,
+    ) -> Result<Option<(usize, PieceWithPos)>, ChessError> {
         if let Some(taken_pawn_square) = self.en_passant_target(piece, offset) {
             if let Some(taken_pawn) = self.pieces.iter().position(|piece| piece.square() == taken_pawn_square) {
-                self.pieces.remove(taken_pawn);
-            } else {
-                return Err(ChessError::InvalidBoard(format!(
-                    "En passant square present at {} but no pawn to take at {}",
-                    piece.square(),
-                    taken_pawn_square
-                )));
+                return Ok(Some((taken_pawn, self.pieces.remove(taken_pawn))));
             }
+            return Err(PositionError::ImpossibleEnPassant(piece.square()).into());
         }
-        Ok(())
+        Ok(None)
     }
 
     /// Check if move was en passant and if so return square of pawn to take
@@ -484,36 +633,12 @@ impl PieceListBoard {
         }
     }
 
-    const KINGSIDE: usize = 0;
-    const QUEENSIDE: usize = 1;
-    const WHITE_CASTLING_RIGHT_OFFSET: usize = 0;
-    const BLACK_CASTLING_RIGHT_OFFSET: usize = 2;
-    const fn castling_right_offset(colour: PieceColour) -> usize {
-        match colour {
-            PieceColour::Black => Self::BLACK_CASTLING_RIGHT_OFFSET,
-            PieceColour::White => Self::WHITE_CASTLING_RIGHT_OFFSET,
-        }
-    }
-
-    fn update_castling_rights(&mut self, piece: PieceWithPos, chess_move: SimpleMove) {
-        let castling_offset = Self::castling_right_offset(piece.colour);
-        match piece.kind {
-            PieceKind::King => {
-                self.castling_rights[castling_offset + Self::KINGSIDE] = false;
-                self.castling_rights[castling_offset + Self::QUEENSIDE] = false;
-            }
-            PieceKind::Rook if chess_move.src().file() == 0 => {
-                self.castling_rights[castling_offset + Self::QUEENSIDE] = false;
-            }
-            PieceKind::Rook if chess_move.src().file() == 7 => {
-                self.castling_rights[castling_offset + Self::KINGSIDE] = false;
-            }
-            _ => (),
-        }
-    }
-
     fn pawn_moves(&self, square: SimpleSquare, colour: PieceColour) -> Result<Vec<SimpleMove>, ChessError> {
         let mut moves: Vec<SimpleMove> = vec![];
+        // Pawns never stand on the last rank, but kings checking for pawn attacks can
+        if (SquareOffset::new(0, 1) * colour).would_overflow(square) {
+            return Ok(moves);
+        }
         let single_push = square + SquareOffset::new(0, 1) * colour;
         let mut takes = vec![];
         if square.file() > 0 {
@@ -584,44 +709,38 @@ impl PieceListBoard {
         Ok(moves)
     }
 
-    fn castle_moves(&self, colour: PieceColour) -> Result<Vec<SimpleMove>, ChessError> {
+    fn castle_moves(&self, king_square: SimpleSquare, colour: PieceColour) -> Result<Vec<SimpleMove>, ChessError> {
         let mut out: Vec<SimpleMove> = vec![];
-        let (back_rank, castle_rights_offset) = match colour {
-            PieceColour::Black => (7, 2),
-            PieceColour::White => (0, 0),
+        let back_rank = match colour {
+            PieceColour::Black => 7,
+            PieceColour::White => 0,
         };
-        let king_square = SimpleSquare::new(4, back_rank);
-
-        let kingside_inbetween = SimpleSquare::new(5, back_rank);
-        let kingside_dest = SimpleSquare::new(6, back_rank);
-
-        let queenside_inbetween = SimpleSquare::new(3, back_rank);
-        let queenside_dest = SimpleSquare::new(2, back_rank);
-        let queenside_knight = SimpleSquare::new(1, back_rank);
-
-        if self.castling_rights[castle_rights_offset + Self::KINGSIDE] {
-            let mut can_castle_kingside = !self.square_under_attack(king_square, colour)?;
-
-            for square in [kingside_inbetween, kingside_dest] {
-                can_castle_kingside &= self.square_empty(square)?;
-                can_castle_kingside &= !self.square_under_attack(square, colour)?;
-            }
-
-            if can_castle_kingside {
-                out.push(SimpleMove::new(king_square, kingside_dest, None));
-            }
+        if king_square.rank() != back_rank {
+            return Ok(out);
         }
-        if self.castling_rights[castle_rights_offset + Self::QUEENSIDE] {
-            let mut can_castle_queenside = !self.square_under_attack(king_square, colour)?;
-
-            for square in [queenside_inbetween, queenside_dest] {
-                can_castle_queenside &= self.square_empty(square)?;
-                can_castle_queenside &= !self.square_under_attack(square, colour)?;
+        for side in [CastlingSide::KingSide, CastlingSide::QueenSide] {
+            let Some(rook_file) = self.castling_rights[castling_index(colour, side)] else {
+                continue;
+            };
+            let rook_square = SimpleSquare::new(rook_file, back_rank);
+            if !matches!(self.get_piece(rook_square), Ok(rook) if rook.kind == PieceKind::Rook && rook.colour == colour)
+            {
+                continue;
             }
-            can_castle_queenside &= self.square_empty(queenside_knight)?;
-
-            if can_castle_queenside {
-                out.push(SimpleMove::new(king_square, queenside_dest, None));
+            let (empty, king_path) = castling::path_files(king_square.file(), rook_file, side);
+            let mut can_castle = true;
+            for file in 0..8 {
+                let square = SimpleSquare::new(file, back_rank);
+                if empty & (1 << file) != 0 {
+                    can_castle &= self.square_empty(square)?;
+                }
+                if king_path & (1 << file) != 0 {
+                    can_castle &= !self.square_under_attack(square, colour)?;
+                }
+            }
+            if can_castle {
+                let dest = castling::king_dest_file(king_square.file(), rook_file, side);
+                out.push(SimpleMove::new(king_square, SimpleSquare::new(dest, back_rank), None));
             }
         }
         Ok(out)
@@ -669,18 +788,34 @@ impl PieceListBoard {
         outstr
     }
 
+    /// Filter pseudo-legal `moves` down to those that don't leave the king in check
+    ///
+    /// Makes and unmakes each move on a single copy of the board.
+    fn legal_subset(&self, moves: impl IntoIterator<Item = SimpleMove>) -> Result<Vec<SimpleMove>, ChessError> {
+        let mut board = self.clone();
+        let mut legal_moves: Vec<SimpleMove> = vec![];
+        for chess_move in moves {
+            let undo = board.make_move(chess_move)?;
+            if !board.king_in_check(self.turn)? {
+                legal_moves.push(chess_move);
+            }
+            board.unmake_move(undo);
+        }
+        Ok(legal_moves)
+    }
+
     fn king_in_check(&self, colour: PieceColour) -> Result<bool, ChessError> {
-        if let Ok(king) = self
+        let kings = self
             .pieces
             .iter()
-            .filter(|piece| piece.kind == PieceKind::King && piece.colour == colour)
-            .exactly_one()
-        {
-            self.square_under_attack(king.square, king.colour)
-        } else {
-            Err(ChessError::InvalidBoard(format!(
-                "Number of kings of colour {colour:?} on the board not equal to one"
-            )))
+            .filter(|piece| piece.kind == PieceKind::King && piece.colour == colour);
+        match kings.clone().exactly_one() {
+            Ok(king) => self.square_under_attack(king.square, king.colour),
+            Err(_) => Err(PositionError::KingCount {
+                colour,
+                count: kings.count(),
+            }
+            .into()),
         }
     }
 
@@ -756,7 +891,9 @@ impl PieceListBoard {
                 promote_to,
                 action,
             } => (piece_kind, src_file, src_rank, takes, dest, promote_to, action),
-            AmbiguousMove::Castle { .. } => panic!("Can't use normal move disambiguator on castle"),
+            AmbiguousMove::Castle { .. } | AmbiguousMove::Drop { .. } => {
+                panic!("Can't use normal move disambiguator on castle or drop")
+            }
         };
         let all_moves: Vec<SimpleMove> = self
             .all_legal_moves()?
@@ -771,7 +908,8 @@ impl PieceListBoard {
                     is_match &= unambiguous_move.src().rank() == rank;
                 }
                 if takes {
-                    is_match &= self.get_piece(unambiguous_move.dest()).is_ok();
+                    let en_passant = piece_kind == PieceKind::Pawn && self.en_passant == Some(dest);
+                    is_match &= self.get_piece(unambiguous_move.dest()).is_ok() || en_passant;
                 }
                 is_match &= unambiguous_move.dest() == dest;
                 is_match &= unambiguous_move.promote_to() == promote_to;
@@ -792,29 +930,29 @@ impl PieceListBoard {
 
     fn disambiguate_castling(&self, chess_move: AmbiguousMove) -> SimpleMove {
         let side = match chess_move {
-            AmbiguousMove::Normal { .. } => panic!("Can't use castling move disambiguator on normal move"),
-            AmbiguousMove::Castle { side } => side,
+            AmbiguousMove::Normal { .. } | AmbiguousMove::Drop { .. } => {
+                panic!("Can't use castling move disambiguator on normal move or drop")
+            }
+            AmbiguousMove::Castle { side, .. } => side,
+        };
+        let rank = match self.turn {
+            PieceColour::Black => 7,
+            PieceColour::White => 0,
+        };
+        // Without the right to castle this gives the standard castling move, which isn't legal
+        let (king_file, dest) = match self.castling_rights[castling_index(self.turn, side)] {
+            Some(rook_file) => {
+                let king_file = self
+                    .pieces
+                    .iter()
+                    .find(|piece| piece.kind == PieceKind::King && piece.colour == self.turn)
+                    .map_or(4, |king| king.square.file());
+                (king_file, castling::king_dest_file(king_file, rook_file, side))
+            }
+            None => (4, castling::final_files(side).0),
         };
-        let mut rank = 0;
-        let mut file = 6;
-        let mut src = SimpleSquare::new(4, 0);
-        if side == CastlingSide::QueenSide {
-            file = 2;
-        }
-        if self.turn == PieceColour::Black {
-            rank = 7;
-            src = SimpleSquare::new(4, 7);
-        }
-
-        SimpleMove::new(src, SimpleSquare::new(file, rank), None)
-    }
 
-    /// Print self as fen string
-    ///
-    /// # Errors
-    /// - [`crate::error::ChessError::InvalidBoard`] if board in invalid state
-    pub fn as_fen_str(&self) -> Result<String, ChessError> {
-        Ok(Fen::try_from(self)?.to_str())
+        SimpleMove::new(SimpleSquare::new(king_file, rank), SimpleSquare::new(dest, rank), None)
     }
 }
 
@@ -824,34 +962,31 @@ impl fmt::Display for PieceListBoard {
     }
 }
 
-impl TryFrom<&PieceListBoard> for Fen {
-    type Error = ChessError;
-
-    fn try_from(value: &PieceListBoard) -> Result<Self, ChessError> {
+impl From<&PieceListBoard> for Fen {
+    fn from(value: &PieceListBoard) -> Self {
         let mut layout: Box<[[Option<SimplePiece>; 8]; 8]> = Box::new([[None; 8]; 8]);
-        for (inverse_rank_number, rank) in layout.iter_mut().enumerate() {
-            for (file_number, piece) in rank.iter_mut().enumerate() {
-                *piece = match value.get_piece(SimpleSquare::new(file_number as u8, 7 - inverse_rank_number as u8)) {
-                    Ok(piece) => Some(SimplePiece::from(piece)),
-                    Err(ChessError::PieceNotFound(_)) => None,
-                    Err(e) => return Err(e),
-                }
-            }
+        for piece in &value.pieces {
+            let square = piece.square();
+            layout[7 - square.rank() as usize][square.file() as usize] = Some(SimplePiece::from(*piece));
         }
-        Ok(Self {
+        Self {
             layout,
+            pockets: None,
+            promoted: 0,
+            checks: None,
             turn: value.turn,
             castling_rights: value.castling_rights,
             en_passant: value.en_passant,
-            halfmove_clock: value.halfmove_clock, // Until board implements 50 move rule
+            halfmove_clock: value.halfmove_clock,
             fullmove_number: value.fullmove_number,
-        })
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::enums::{DrawReason, GameOutcome, WinReason};
 
     fn moves_from_strs(moves: Vec<&str>) -> Vec<SimpleMove> {
         let mut new_moves: Vec<SimpleMove> = moves
@@ -891,10 +1026,11 @@ mod tests {
             ],
             turn: PieceColour::White,
             en_passant: None,
-            castling_rights: [false, false, false, false],
+            castling_rights: [None, None, None, None],
             halfmove_clock: 0,
             fullmove_number: 1,
             board_history: vec![],
+            hash: 0,
         };
         let e = board.get_piece(square).unwrap_err();
         match e {
@@ -910,10 +1046,11 @@ mod tests {
             pieces: vec![],
             turn: PieceColour::White,
             en_passant: None,
-            castling_rights: [false, false, false, false],
+            castling_rights: [None, None, None, None],
             halfmove_clock: 0,
             fullmove_number: 1,
             board_history: vec![],
+            hash: 0,
         };
         let e = board.get_piece(square).unwrap_err();
         match e {
@@ -976,7 +1113,8 @@ mod tests {
 
     #[test]
     fn pawn_takes_en_passant_behind() {
-        let board = PieceListBoard::from_fen("rnbqkbnr/pppppppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq e3 0 2").unwrap();
+        let board = PieceListBoard::from_fen_unchecked("rnbqkbnr/pppppppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq e3 0 2")
+            .unwrap();
         let mut moves: Vec<SimpleMove> = board
             .piece_plegal_moves(SimpleSquare::from_pgn_str("e4").unwrap())
             .unwrap()
@@ -1071,7 +1209,7 @@ mod tests {
 
     #[test]
     fn king_in_check() {
-        let board = PieceListBoard::from_fen("k3r3/1P6/4K3/8/8/8/8/8 w - - 0 2").unwrap();
+        let board = PieceListBoard::from_fen_unchecked("k3r3/1P6/4K3/8/8/8/8/8 w - - 0 2").unwrap();
         assert!(board.king_in_check(PieceColour::White).unwrap());
         assert!(board.king_in_check(PieceColour::Black).unwrap());
         assert_eq!(board.state().unwrap(), BoardState::Check);
@@ -1126,9 +1264,9 @@ mod tests {
         let mut board =
             PieceListBoard::from_fen("rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq e6 0 1").unwrap();
         board.move_piece(SimpleMove::from_pgn_str("e1e2").unwrap()).unwrap();
-        assert_eq!(board.castling_rights, [false, false, true, true]);
+        assert_eq!(board.castling_rights, [None, None, Some(7), Some(0)]);
         board.move_piece(SimpleMove::from_pgn_str("e8e7").unwrap()).unwrap();
-        assert_eq!(board.castling_rights, [false, false, false, false]);
+        assert_eq!(board.castling_rights, [None, None, None, None]);
     }
 
     #[test]
@@ -1136,9 +1274,9 @@ mod tests {
         let mut board =
             PieceListBoard::from_fen("r1bqkbnr/pppppppp/2n5/8/8/2N5/PPPPPPPP/R1BQKBNR w KQkq - 0 1").unwrap();
         board.move_piece(SimpleMove::from_pgn_str("a1b1").unwrap()).unwrap();
-        assert_eq!(board.castling_rights, [true, false, true, true]);
+        assert_eq!(board.castling_rights, [Some(7), None, Some(7), Some(0)]);
         board.move_piece(SimpleMove::from_pgn_str("a8b8").unwrap()).unwrap();
-        assert_eq!(board.castling_rights, [true, false, true, false]);
+        assert_eq!(board.castling_rights, [Some(7), None, Some(7), None]);
     }
 
     #[test]
@@ -1146,49 +1284,144 @@ mod tests {
         let mut board =
             PieceListBoard::from_fen("rnbqkb1r/pppppppp/5n2/8/8/5N2/PPPPPPPP/RNBQKB1R w KQkq - 0 1").unwrap();
         board.move_piece(SimpleMove::from_pgn_str("h1g1").unwrap()).unwrap();
-        assert_eq!(board.castling_rights, [false, true, true, true]);
+        assert_eq!(board.castling_rights, [None, Some(0), Some(7), Some(0)]);
         board.move_piece(SimpleMove::from_pgn_str("h8g8").unwrap()).unwrap();
-        assert_eq!(board.castling_rights, [false, true, false, true]);
+        assert_eq!(board.castling_rights, [None, Some(0), None, Some(0)]);
+    }
+
+    #[test]
+    fn castling_invalidation_rook_taken() {
+        let mut board = PieceListBoard::from_fen("4k2r/8/8/8/8/8/8/R3K2R w KQk - 0 1").unwrap();
+        board.move_piece(SimpleMove::from_pgn_str("h1h8").unwrap()).unwrap();
+        assert_eq!(board.castling_rights, [None, Some(0), None, None]);
+    }
+
+    #[test]
+    fn king_on_last_rank() {
+        let board = PieceListBoard::from_fen("4K3/8/8/8/8/8/8/4k3 w - - 0 1").unwrap();
+        assert!(!board.king_in_check(PieceColour::White).unwrap());
+        assert!(!board.king_in_check(PieceColour::Black).unwrap());
+    }
+
+    #[test]
+    fn unmake_en_passant() {
+        let mut board =
+            PieceListBoard::from_fen("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3").unwrap();
+        let original = board.clone();
+        let undo = board.make_move(SimpleMove::from_pgn_str("e5f6").unwrap()).unwrap();
+        assert_eq!(board.pieces.len(), 31);
+        board.unmake_move(undo);
+        assert_eq!(board, original);
     }
 
     #[test]
     fn fifty_move_draw() {
-        let mut board = PieceListBoard::starting_board();
-        for _ in 0..12 {
-            board.move_piece(SimpleMove::from_pgn_str("g1f3").unwrap()).unwrap();
-            board.move_piece(SimpleMove::from_pgn_str("g8f6").unwrap()).unwrap();
-            board.move_piece(SimpleMove::from_pgn_str("f3g1").unwrap()).unwrap();
-            board.move_piece(SimpleMove::from_pgn_str("f6g8").unwrap()).unwrap();
-        }
-        board.move_piece(SimpleMove::from_pgn_str("g1f3").unwrap()).unwrap();
-        board.move_piece(SimpleMove::from_pgn_str("g8f6").unwrap()).unwrap();
-        assert_eq!(board.state().unwrap(), BoardState::Stalemate);
+        let mut board = PieceListBoard::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 98 80").unwrap();
+        board.move_piece(SimpleMove::from_pgn_str("a1a2").unwrap()).unwrap();
+        assert_eq!(board.outcome().unwrap(), GameOutcome::Ongoing);
+        board.move_piece(SimpleMove::from_pgn_str("e8d8").unwrap()).unwrap();
+        assert_eq!(
+            board.outcome().unwrap(),
+            GameOutcome::ClaimableDraw(DrawReason::FiftyMoveRule)
+        );
+        assert_eq!(board.state().unwrap(), BoardState::Normal);
+        assert!(board.all_legal_moves().unwrap().into_iter().next().is_some());
     }
 
     #[test]
     fn fifty_move_not_draw() {
+        let mut board = PieceListBoard::from_fen("4k3/p7/8/8/8/8/8/R3K3 b - - 99 80").unwrap();
+        board.move_piece(SimpleMove::from_pgn_str("a7a6").unwrap()).unwrap();
+        assert_eq!(board.outcome().unwrap(), GameOutcome::Ongoing);
+    }
+
+    #[test]
+    fn seventy_five_move_draw() {
+        let mut board = PieceListBoard::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 149 80").unwrap();
+        board.move_piece(SimpleMove::from_pgn_str("a1a2").unwrap()).unwrap();
+        assert_eq!(
+            board.outcome().unwrap(),
+            GameOutcome::Draw(DrawReason::SeventyFiveMoveRule)
+        );
+    }
+
+    #[test]
+    fn checkmate_beats_seventy_five_move_draw() {
+        let mut board = PieceListBoard::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 149 80").unwrap();
+        board.move_piece(SimpleMove::from_pgn_str("a1a8").unwrap()).unwrap();
+        assert_eq!(
+            board.outcome().unwrap(),
+            GameOutcome::Win {
+                winner: PieceColour::White,
+                reason: WinReason::Checkmate
+            }
+        );
+    }
+
+    #[test]
+    fn threefold_repetition() {
         let mut board = PieceListBoard::starting_board();
-        for _ in 0..12 {
+        for _ in 0..2 {
             board.move_piece(SimpleMove::from_pgn_str("g1f3").unwrap()).unwrap();
             board.move_piece(SimpleMove::from_pgn_str("g8f6").unwrap()).unwrap();
             board.move_piece(SimpleMove::from_pgn_str("f3g1").unwrap()).unwrap();
             board.move_piece(SimpleMove::from_pgn_str("f6g8").unwrap()).unwrap();
         }
-        board.move_piece(SimpleMove::from_pgn_str("g1f3").unwrap()).unwrap();
-        board.move_piece(SimpleMove::from_pgn_str("e7e5").unwrap()).unwrap();
-        board.move_piece(SimpleMove::from_pgn_str("f3g1").unwrap()).unwrap();
-        assert_eq!(board.state().unwrap(), BoardState::Normal);
+        assert_eq!(board.repetition_count(), 3);
+        assert_eq!(
+            board.outcome().unwrap(),
+            GameOutcome::ClaimableDraw(DrawReason::ThreefoldRepetition)
+        );
+        assert_eq!(board.all_legal_moves().unwrap().into_iter().count(), 20);
     }
 
     #[test]
-    fn threefold_repetition() {
+    fn fivefold_repetition() {
         let mut board = PieceListBoard::starting_board();
-        for _ in 0..3 {
+        for _ in 0..4 {
             board.move_piece(SimpleMove::from_pgn_str("g1f3").unwrap()).unwrap();
             board.move_piece(SimpleMove::from_pgn_str("g8f6").unwrap()).unwrap();
             board.move_piece(SimpleMove::from_pgn_str("f3g1").unwrap()).unwrap();
             board.move_piece(SimpleMove::from_pgn_str("f6g8").unwrap()).unwrap();
         }
+        assert_eq!(
+            board.outcome().unwrap(),
+            GameOutcome::Draw(DrawReason::FivefoldRepetition)
+        );
+    }
+
+    #[test]
+    fn stalemate() {
+        let board = PieceListBoard::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
         assert_eq!(board.state().unwrap(), BoardState::Stalemate);
+        assert_eq!(board.outcome().unwrap(), GameOutcome::Draw(DrawReason::Stalemate));
+    }
+
+    #[test]
+    fn insufficient_material() {
+        for fen in [
+            "4k3/8/8/8/8/8/8/4K3 w - - 0 1",
+            "4k3/8/8/8/8/8/8/4KN2 w - - 0 1",
+            "4kb2/8/8/8/8/8/8/4K3 w - - 0 1",
+            "4kb2/8/8/8/8/8/8/2B1K3 w - - 0 1",
+        ] {
+            let board = PieceListBoard::from_fen(fen).unwrap();
+            assert!(board.insufficient_material(), "{fen}");
+            assert_eq!(
+                board.outcome().unwrap(),
+                GameOutcome::Draw(DrawReason::InsufficientMaterial)
+            );
+        }
+        for fen in [
+            "4k3/8/8/8/8/8/8/3NKN2 w - - 0 1",
+            "4k3/8/8/8/8/8/8/2B1KB2 w - - 0 1",
+            "4k3/8/8/8/8/8/8/4KP2 w - - 0 1",
+            "4kn2/8/8/8/8/8/8/4KB2 w - - 0 1",
+        ] {
+            assert!(
+                !PieceListBoard::from_fen_unchecked(fen).unwrap().insufficient_material(),
+                "{fen}"
+            );
+        }
     }
 }
@@ -0,0 +1,206 @@
+//! Zobrist hashing of chess positions
+//!
+//! Every piece on every square, black to move, each castling right with each rook file and each en
+//! passant file is given a random 64-bit key, and the hash of a position is the xor of all the keys
+//! that apply to it. Since xor is its own inverse, making a move only has to xor in and out the
+//! keys that change, so boards keep their hash up to date as moves are made rather than
+//! recalculating it.
+//!
+//! The en passant file is included whenever an en passant square is set, even if no pawn can take
+//! on it. Castling rights are keyed on the file of their rook, so Chess960 positions that differ
+//! only in which rook can castle hash differently. The keys are generated at compile time by a
+//! fixed seed, so hashes are stable between runs and can be stored.
+//!
+//! Crazyhouse boards also hash the pieces in hand, with a key for each count of each kind of piece
+//! in each pocket, and which pieces were promoted from pawns, with a key for each square.
+//! Three-check boards hash the number of checks each side has given.
+
+use crate::enums::PieceColour;
+use crate::notation::fen::Fen;
+use crate::simple_types::SimpleSquare;
+use crate::traits::{ChessPiece, ChessSquare};
+
+const PIECE_KEYS_LEN: usize = 2 * 6 * 64;
+const TURN_KEY_INDEX: usize = PIECE_KEYS_LEN;
+const CASTLING_KEYS_START: usize = TURN_KEY_INDEX + 1;
+const EN_PASSANT_KEYS_START: usize = CASTLING_KEYS_START + 4 * 8;
+const POCKET_KEYS_START: usize = EN_PASSANT_KEYS_START + 8;
+/// Highest count of a kind of piece in a pocket with its own key, larger counts share it
+const POCKET_MAX_COUNT: usize = 16;
+const PROMOTED_KEYS_START: usize = POCKET_KEYS_START + 2 * 6 * POCKET_MAX_COUNT;
+const CHECKS_KEYS_START: usize = PROMOTED_KEYS_START + 64;
+/// Highest number of checks given with its own key, more checks share it
+const CHECKS_MAX_COUNT: usize = 3;
+const KEYS_LEN: usize = CHECKS_KEYS_START + 2 * CHECKS_MAX_COUNT;
+
+const KEYS: [u64; KEYS_LEN] = generate_keys(0x2545_f491_4f6c_dd1d);
+
+/// Fill the key table using the SplitMix64 generator seeded with `seed`
+const fn generate_keys(seed: u64) -> [u64; KEYS_LEN] {
+    let mut keys = [0; KEYS_LEN];
+    let mut state = seed;
+    let mut i = 0;
+    while i < KEYS_LEN {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut key = state;
+        key = (key ^ (key >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        key = (key ^ (key >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        keys[i] = key ^ (key >> 31);
+        i += 1;
+    }
+    keys
+}
+
+/// Key for `piece` standing on `square`
+pub fn piece_key(piece: &impl ChessPiece, square: &impl ChessSquare) -> u64 {
+    let index = (piece.colour() as usize * 6 + piece.kind() as usize) * 64
+        + square.rank() as usize * 8
+        + square.file() as usize;
+    KEYS[index]
+}
+
+/// Key for the side to move, zero when it's white's turn
+pub fn turn_key(turn: PieceColour) -> u64 {
+    match turn {
+        PieceColour::Black => KEYS[TURN_KEY_INDEX],
+        PieceColour::White => 0,
+    }
+}
+
+/// Combined key for all held castling rights, given by the file of their rook in the order white
+/// kingside, white queenside, black kingside, black queenside
+pub fn castling_key(castling_rights: [Option<u8>; 4]) -> u64 {
+    castling_rights
+        .into_iter()
+        .enumerate()
+        .filter_map(|(right, file)| file.map(|file| KEYS[CASTLING_KEYS_START + right * 8 + usize::from(file)]))
+        .fold(0, |key, right_key| key ^ right_key)
+}
+
+/// Key for the file of the en passant square, zero if there isn't one
+pub fn en_passant_key(en_passant: Option<impl ChessSquare>) -> u64 {
+    en_passant.map_or(0, |square| KEYS[EN_PASSANT_KEYS_START + square.file() as usize])
+}
+
+/// Key for `count` pieces like `piece` in hand in Crazyhouse, zero for none
+pub fn pocket_key(piece: &impl ChessPiece, count: u8) -> u64 {
+    match count {
+        0 => 0,
+        count => {
+            let index = (piece.colour() as usize * 6 + piece.kind() as usize) * POCKET_MAX_COUNT
+                + usize::from(count).min(POCKET_MAX_COUNT)
+                - 1;
+            KEYS[POCKET_KEYS_START + index]
+        }
+    }
+}
+
+/// Combined key for the squares of pieces promoted from pawns in Crazyhouse, as bits indexed by
+/// rank * 8 + file
+pub fn promoted_key(mut promoted: u64) -> u64 {
+    let mut key = 0;
+    while promoted != 0 {
+        key ^= KEYS[PROMOTED_KEYS_START + promoted.trailing_zeros() as usize];
+        promoted &= promoted - 1;
+    }
+    key
+}
+
+/// Key for `colour` having given `count` checks in Three-check, zero for none
+pub fn checks_key(colour: PieceColour, count: u8) -> u64 {
+    match count {
+        0 => 0,
+        count => {
+            let index = colour as usize * CHECKS_MAX_COUNT + usize::from(count).min(CHECKS_MAX_COUNT) - 1;
+            KEYS[CHECKS_KEYS_START + index]
+        }
+    }
+}
+
+/// Combined key for everything but the pieces
+pub fn state_key(turn: PieceColour, castling_rights: [Option<u8>; 4], en_passant: Option<impl ChessSquare>) -> u64 {
+    turn_key(turn) ^ castling_key(castling_rights) ^ en_passant_key(en_passant)
+}
+
+/// Hash of the position described by `fen`
+pub(crate) fn fen_hash(fen: &Fen) -> u64 {
+    let mut hash = state_key(fen.turn, fen.castling_rights, fen.en_passant);
+    for (inverse_rank, rank) in fen.layout.iter().enumerate() {
+        for (file, piece) in rank.iter().enumerate() {
+            if let Some(piece) = piece {
+                hash ^= piece_key(piece, &SimpleSquare::new(file as u8, 7 - inverse_rank as u8));
+            }
+        }
+    }
+    hash
+}
+
+/// Number of times the position with `hash` appears in `history`
+///
+/// `history` holds the hashes of every earlier position, oldest first. Only the last
+/// `halfmove_clock` positions are checked since no position from before a capture or pawn move can
+/// come up again, and only every other one of those since the side to move must be the same.
+pub(crate) fn repetitions(history: &[u64], hash: u64, halfmove_clock: u32) -> usize {
+    history
+        .iter()
+        .rev()
+        .take(halfmove_clock as usize)
+        .skip(1)
+        .step_by(2)
+        .filter(|&&earlier| earlier == hash)
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn keys_unique() {
+        assert_eq!(KEYS.iter().collect::<HashSet<_>>().len(), KEYS_LEN);
+        assert!(!KEYS.contains(&0));
+    }
+
+    #[test]
+    fn empty_state() {
+        assert_eq!(state_key(PieceColour::White, [None; 4], None::<SimpleSquare>), 0);
+        assert_ne!(state_key(PieceColour::Black, [None; 4], None::<SimpleSquare>), 0);
+    }
+
+    #[test]
+    fn repetitions_since_irreversible_move() {
+        let history = [1, 2, 1, 3, 4, 5];
+        assert_eq!(repetitions(&history, 1, 6), 2);
+        assert_eq!(repetitions(&history, 1, 4), 1);
+        // Same position with the other side to move can't be a repetition
+        assert_eq!(repetitions(&history, 2, 6), 0);
+        assert_eq!(repetitions(&[], 1, 20), 0);
+    }
+
+    #[test]
+    fn castling_rook_file() {
+        let hash = |fen: &str| fen_hash(&fen.parse::<Fen>().unwrap());
+        assert_ne!(
+            hash("4k3/8/8/8/8/8/8/RR2K3 w A - 0 1"),
+            hash("4k3/8/8/8/8/8/8/RR2K3 w B - 0 1")
+        );
+        assert_ne!(
+            castling_key([Some(7), None, None, None]),
+            castling_key([None, None, Some(7), None])
+        );
+    }
+
+    #[test]
+    fn en_passant_file_only() {
+        assert_eq!(
+            en_passant_key(Some(SimpleSquare::new(4, 2))),
+            en_passant_key(Some(SimpleSquare::new(4, 5)))
+        );
+        assert_ne!(
+            en_passant_key(Some(SimpleSquare::new(4, 2))),
+            en_passant_key(Some(SimpleSquare::new(3, 2)))
+        );
+    }
+}
@@ -120,6 +120,64 @@ pub enum BoardState {
     Checkmate,
 }
 
+/// Reason for a game ending in, or being claimable as, a draw
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DrawReason {
+    /// Side to move has no legal moves but isn't in check
+    Stalemate,
+    /// Neither side has enough pieces left to checkmate
+    InsufficientMaterial,
+    /// Neither side can ever checkmate despite having the material to, such as when the pawns are
+    /// locked together and the kings can't get to them
+    DeadPosition,
+    /// Fifty moves by each side without a capture or pawn move, can be claimed
+    FiftyMoveRule,
+    /// Seventy five moves by each side without a capture or pawn move, drawn automatically
+    SeventyFiveMoveRule,
+    /// Same position reached three times, can be claimed
+    ThreefoldRepetition,
+    /// Same position reached five times, drawn automatically
+    FivefoldRepetition,
+}
+
+/// Reason for a game being won
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum WinReason {
+    /// Side to move is in check and has no legal moves
+    Checkmate,
+    /// Winner gave check for the third time in Three-check
+    ThreeCheck,
+    /// Winner's king reached one of the four centre squares in King of the Hill
+    KingOfTheHill,
+}
+
+/// Outcome of a game from its current position
+///
+/// Unlike [`BoardState`] this takes the rules for drawn games into account, as well as king safety.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum GameOutcome {
+    /// Game continues with no draw available to claim
+    Ongoing,
+    /// Game continues, but the side to move may claim a draw
+    ClaimableDraw(DrawReason),
+    /// Game is over in a draw
+    Draw(DrawReason),
+    /// Game is over and won by `winner`
+    Win {
+        /// Colour of the side that won
+        winner: PieceColour,
+        /// How the game was won
+        reason: WinReason,
+    },
+}
+
+impl GameOutcome {
+    /// Whether the game is over, claimable draws are not counted as over until they are claimed
+    pub fn is_over(&self) -> bool {
+        matches!(self, Self::Draw(_) | Self::Win { .. })
+    }
+}
+
 impl From<MoveAction> for BoardState {
     fn from(value: MoveAction) -> Self {
         match value {
@@ -218,6 +276,17 @@ pub enum AmbiguousMove {
     Castle {
         /// Side to castle on
         side: CastlingSide,
+        /// The action resulting from the move (check, checkmate)
+        action: Option<MoveAction>,
+    },
+    /// Piece put onto the board from the pocket in Crazyhouse, such as `N@f3`
+    Drop {
+        /// The type of piece dropped
+        piece_kind: PieceKind,
+        /// The square dropped on
+        dest: SimpleSquare,
+        /// The action resulting from the move (check, checkmate)
+        action: Option<MoveAction>,
     },
 }
 
@@ -260,7 +329,33 @@ impl AmbiguousMove {
                 }
                 s
             }
-            AmbiguousMove::Castle { side } => side.as_str().to_string(),
+            AmbiguousMove::Castle { side, action } => {
+                let mut s = side.as_str().to_string();
+                if let Some(a) = action {
+                    s.push(char::from(*a));
+                }
+                s
+            }
+            AmbiguousMove::Drop {
+                piece_kind,
+                dest,
+                action,
+            } => {
+                let mut s = format!("{}@{}", char::from(*piece_kind), dest.as_str());
+                if let Some(a) = action {
+                    s.push(char::from(*a));
+                }
+                s
+            }
+        }
+    }
+
+    /// Check or checkmate the move is annotated with
+    pub fn action(&self) -> Option<MoveAction> {
+        match self {
+            AmbiguousMove::Normal { action, .. }
+            | AmbiguousMove::Castle { action, .. }
+            | AmbiguousMove::Drop { action, .. } => *action,
         }
     }
 
@@ -271,20 +366,20 @@ impl AmbiguousMove {
         use proptest::{option::of, prelude::any};
 
         let piece_kind = PieceKind::strategy();
-        let src_file = of(any::<u8>().prop_filter("Valid range for file", |x| (0..=7).contains(x)));
-        let src_rank = of(any::<u8>().prop_filter("Valid range for rank", |x| (0..=7).contains(x)));
-        let castle = any::<bool>();
+        let src_file = of(0..=7u8);
+        let src_rank = of(0..=7u8);
+        let move_type = 0..3u8;
         let castling_side = CastlingSide::strategy();
         let takes = any::<bool>();
         let dest = SimpleSquare::strategy();
         let promote_to = of(PieceKind::promotable_stategy());
         let action = of(MoveAction::strategy());
-        (castle, castling_side, piece_kind, src_file, src_rank, takes, dest, promote_to, action).prop_map(
-            |(castle,castling_side, piece_kind, src_file, src_rank, takes, dest, promote_to, action,)| {
-                if castle {
-                    AmbiguousMove::Castle { side: castling_side }
-                } else {
-                    AmbiguousMove::Normal {piece_kind, src_file, src_rank, takes, dest, promote_to, action }
+        (move_type, castling_side, piece_kind, src_file, src_rank, takes, dest, promote_to, action).prop_map(
+            |(move_type, castling_side, piece_kind, src_file, src_rank, takes, dest, promote_to, action,)| {
+                match move_type {
+                    0 => AmbiguousMove::Castle { side: castling_side, action },
+                    1 if piece_kind != PieceKind::King => AmbiguousMove::Drop { piece_kind, dest, action },
+                    _ => AmbiguousMove::Normal {piece_kind, src_file, src_rank, takes, dest, promote_to, action },
                 }
             }
         )
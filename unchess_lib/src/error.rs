@@ -1,12 +1,34 @@
 //! Library wide error handling
 use std::fmt::Debug;
+use std::io;
 use thiserror::Error;
 
 use crate::{
-    enums::{AmbiguousMove, BoardState},
+    enums::{AmbiguousMove, BoardState, CastlingSide, GameOutcome, PieceColour, PieceKind},
+    notation::pgn::GameResult,
     simple_types::{SimpleMove, SimpleSquare},
 };
 
+/// Rules broken by a position that can't be reached in a game of chess
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[allow(missing_docs)] // Enum variants self documented by error messages
+pub enum PositionError {
+    #[error("{colour:?} has {count} kings rather than one")]
+    KingCount { colour: PieceColour, count: usize },
+
+    #[error("Pawn on first or last rank at {0}")]
+    PawnOnBackRank(SimpleSquare),
+
+    #[error("{0:?} king in check with the other side to move")]
+    OpponentInCheck(PieceColour),
+
+    #[error("{colour:?} can castle {side:?} without king and rook on their starting squares")]
+    CastlingWithoutPieces { colour: PieceColour, side: CastlingSide },
+
+    #[error("En passant square {0} not behind a pawn that just moved two squares")]
+    ImpossibleEnPassant(SimpleSquare),
+}
+
 #[derive(Error, Debug)]
 /// Errors common across library interfaces
 #[allow(missing_docs)] // Enum variants self documented by error messages
@@ -20,6 +42,9 @@ pub enum ChessError {
     #[error("Illegal move {0:?}")]
     IllegalMove(SimpleMove),
 
+    #[error("Illegal drop of {0:?} on {1}")]
+    IllegalDrop(PieceKind, SimpleSquare),
+
     #[error("Impossible move {0}")]
     ImpossibleMove(AmbiguousMove),
 
@@ -35,9 +60,61 @@ pub enum ChessError {
     #[error("{0:?} is not an actionable move")]
     NotAction(BoardState),
 
+    #[error("Capture {given} not marked with x, expected {expected}")]
+    MissingCaptureMarker {
+        given: AmbiguousMove,
+        expected: AmbiguousMove,
+    },
+
+    #[error("Move {given} marked with x but takes nothing, expected {expected}")]
+    UnexpectedCaptureMarker {
+        given: AmbiguousMove,
+        expected: AmbiguousMove,
+    },
+
+    #[error("Move {given} disambiguated more than needed, expected {expected}")]
+    OverDisambiguated {
+        given: AmbiguousMove,
+        expected: AmbiguousMove,
+    },
+
+    #[error("Move {given} missing check or checkmate suffix, expected {expected}")]
+    MissingCheckSuffix {
+        given: AmbiguousMove,
+        expected: AmbiguousMove,
+    },
+
+    #[error("Move {given} has wrong check or checkmate suffix, expected {expected}")]
+    WrongCheckSuffix {
+        given: AmbiguousMove,
+        expected: AmbiguousMove,
+    },
+
     #[error("Invalid PGN: {0}")]
     InvalidPGN(String),
 
+    #[error("Invalid PGN at byte {offset}, line {line}, near {near:?}")]
+    InvalidPGNAt { offset: u64, line: u64, near: String },
+
+    #[error("Result {result} doesn't match the final position, {outcome:?}")]
+    ResultMismatch { result: GameResult, outcome: GameOutcome },
+
+    #[error("Termination {termination:?} doesn't match result {result} and final position, {outcome:?}")]
+    TerminationMismatch {
+        termination: String,
+        result: GameResult,
+        outcome: GameOutcome,
+    },
+
     #[error("Invalid FEN: {0}")]
     InvalidFEN(String),
+
+    #[error("Invalid position: {0}")]
+    InvalidPosition(#[from] PositionError),
+
+    #[error("Invalid EPD: {0}")]
+    InvalidEPD(String),
+
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
 }
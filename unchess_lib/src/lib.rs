@@ -7,6 +7,7 @@ pub mod notation;
 mod parser;
 pub mod simple_types;
 pub mod traits;
+pub mod variant;
 
 #[cfg(doctest)]
 #[doc = include_str!("../../Readme.md")]
@@ -0,0 +1,299 @@
+//! Extended Position Description
+//!
+//! An [`Epd`] record is the first four fields of a FEN, giving the position without its move
+//! counters, followed by operations each made of an opcode, its operands and a `;`, such as
+//! `bm Qg6; id "WAC.001";`. Test suites and tuning datasets are commonly given as files with one
+//! record on each line.
+//!
+//! Every operation is kept as the opcode and its operands in the order they were given, with typed
+//! access to the common ones: best and avoid moves `bm` and `am`, the record identifier `id`,
+//! comments `c0` to `c9`, and the analysis depth `acd` and centipawn evaluation `ce`. The counters
+//! given by `hmvc` and `fmvn` are used for the board of the position.
+
+use core::fmt;
+use core::str::FromStr;
+
+use crate::enums::AmbiguousMove;
+use crate::error::ChessError;
+use crate::notation::fen::Fen;
+use crate::notation::pgn::write_string;
+use crate::parser;
+use crate::traits::ChessBoard;
+
+/// Opcodes whose operands are strings, always written in quotes
+const STRING_OPCODES: [&str; 11] = ["id", "c0", "c1", "c2", "c3", "c4", "c5", "c6", "c7", "c8", "c9"];
+
+/// Position and operations of an EPD record
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Epd {
+    fen: Fen,
+    operations: Vec<(String, Vec<String>)>,
+}
+
+impl Epd {
+    /// Parse a single EPD record
+    ///
+    /// # Errors
+    /// - [`crate::error::ChessError::InvalidEPD`] if the record can't be parsed
+    pub fn from_epd_str(input: &str) -> Result<Self, ChessError> {
+        match parser::epd::epd(input) {
+            Ok(("", (fen, operations))) => Ok(Self { fen, operations }),
+            _ => Err(ChessError::InvalidEPD(input.to_string())),
+        }
+    }
+
+    /// Record for the position of a FEN with no operations, dropping the move counters
+    ///
+    /// # Errors
+    /// - [`crate::error::ChessError::InvalidFEN`] if `fen` isn't valid
+    pub fn from_fen_str(fen: &str) -> Result<Self, ChessError> {
+        match parser::fen::fen(fen) {
+            Ok((rest, fen)) if rest.trim().is_empty() => Ok(Self {
+                fen,
+                operations: vec![],
+            }),
+            _ => Err(ChessError::InvalidFEN(fen.to_string())),
+        }
+    }
+
+    /// FEN of the position, with the move counters given by `hmvc` and `fmvn` or 0 and 1
+    pub fn fen_str(&self) -> String {
+        Fen {
+            halfmove_clock: self.number("hmvc").unwrap_or(0),
+            fullmove_number: self.number("fmvn").unwrap_or(1),
+            ..self.fen.clone()
+        }
+        .to_string()
+    }
+
+    /// Board of the position, with the move counters given by `hmvc` and `fmvn` or 0 and 1
+    ///
+    /// # Errors
+    /// - [`crate::error::ChessError::InvalidPosition`] if the position breaks a rule checked by
+    ///   [`Fen::validate`]
+    pub fn board<B: ChessBoard>(&self) -> Result<B, ChessError> {
+        self.fen.validate()?;
+        Ok(B::from(Fen {
+            halfmove_clock: self.number("hmvc").unwrap_or(0),
+            fullmove_number: self.number("fmvn").unwrap_or(1),
+            ..self.fen.clone()
+        }))
+    }
+
+    /// Operations as opcode and operands, in the order they were given
+    pub fn operations(&self) -> &[(String, Vec<String>)] {
+        &self.operations
+    }
+
+    /// Operands of the operation `opcode`, if present
+    pub fn operation(&self, opcode: &str) -> Option<&[String]> {
+        self.operations
+            .iter()
+            .find(|(name, _)| name == opcode)
+            .map(|(_, operands)| operands.as_slice())
+    }
+
+    /// Set the operands of the operation `opcode`, replacing any it already had
+    pub fn set_operation(&mut self, opcode: &str, operands: Vec<String>) {
+        if let Some((_, old_operands)) = self.operations.iter_mut().find(|(name, _)| name == opcode) {
+            *old_operands = operands;
+        } else {
+            self.operations.push((opcode.to_string(), operands));
+        }
+    }
+
+    /// Remove the operation `opcode`, returning its operands if it was present
+    pub fn remove_operation(&mut self, opcode: &str) -> Option<Vec<String>> {
+        let index = self.operations.iter().position(|(name, _)| name == opcode)?;
+        Some(self.operations.remove(index).1)
+    }
+
+    /// Best moves given by `bm`, empty if not present
+    ///
+    /// # Errors
+    /// - [`crate::error::ChessError::InvalidEPD`] if a move isn't in standard algebraic notation
+    pub fn best_moves(&self) -> Result<Vec<AmbiguousMove>, ChessError> {
+        self.moves("bm")
+    }
+
+    /// Set the best moves given by `bm`
+    pub fn set_best_moves(&mut self, moves: &[AmbiguousMove]) {
+        self.set_operation("bm", moves.iter().map(AmbiguousMove::as_pgn_str).collect());
+    }
+
+    /// Moves to avoid given by `am`, empty if not present
+    ///
+    /// # Errors
+    /// - [`crate::error::ChessError::InvalidEPD`] if a move isn't in standard algebraic notation
+    pub fn avoid_moves(&self) -> Result<Vec<AmbiguousMove>, ChessError> {
+        self.moves("am")
+    }
+
+    /// Set the moves to avoid given by `am`
+    pub fn set_avoid_moves(&mut self, moves: &[AmbiguousMove]) {
+        self.set_operation("am", moves.iter().map(AmbiguousMove::as_pgn_str).collect());
+    }
+
+    /// Record identifier given by `id`
+    pub fn id(&self) -> Option<&str> {
+        self.string("id")
+    }
+
+    /// Set the record identifier given by `id`
+    pub fn set_id(&mut self, id: &str) {
+        self.set_operation("id", vec![id.to_string()]);
+    }
+
+    /// Comment given by `c0` to `c9`
+    pub fn comment(&self, n: u8) -> Option<&str> {
+        self.string(&format!("c{n}"))
+    }
+
+    /// Set the comment given by `c0` to `c9`
+    ///
+    /// # Panics
+    /// Panics if `n` > 9
+    pub fn set_comment(&mut self, n: u8, comment: &str) {
+        assert!(n <= 9, "Comment opcodes only go up to c9");
+        self.set_operation(&format!("c{n}"), vec![comment.to_string()]);
+    }
+
+    /// Analysis count depth given by `acd`, if present and a number
+    pub fn acd(&self) -> Option<u32> {
+        self.number("acd")
+    }
+
+    /// Set the analysis count depth given by `acd`
+    pub fn set_acd(&mut self, depth: u32) {
+        self.set_operation("acd", vec![depth.to_string()]);
+    }
+
+    /// Centipawn evaluation given by `ce` from the point of view of the side to move, if present
+    /// and a number
+    pub fn ce(&self) -> Option<i32> {
+        self.number("ce")
+    }
+
+    /// Set the centipawn evaluation given by `ce`
+    pub fn set_ce(&mut self, evaluation: i32) {
+        self.set_operation("ce", vec![evaluation.to_string()]);
+    }
+
+    /// Return as string in EPD format
+    ///
+    /// Operands of `id` and `c0` to `c9` are always quoted, and other operands only when they
+    /// contain whitespace, `;` or quotes.
+    pub fn as_epd_str(&self) -> String {
+        let mut s = self.fen.position_str();
+        for (opcode, operands) in &self.operations {
+            s.push(' ');
+            s.push_str(opcode);
+            for operand in operands {
+                s.push(' ');
+                let plain = !operand.is_empty()
+                    && !operand.contains(|c: char| c.is_whitespace() || matches!(c, ';' | '"' | '\\'));
+                if plain && !STRING_OPCODES.contains(&opcode.as_str()) {
+                    s.push_str(operand);
+                } else {
+                    write_string(&mut s, operand);
+                }
+            }
+            s.push(';');
+        }
+        s
+    }
+
+    /// Single operand of the operation `opcode`
+    fn string(&self, opcode: &str) -> Option<&str> {
+        match self.operation(opcode)? {
+            [operand] => Some(operand),
+            _ => None,
+        }
+    }
+
+    /// Single operand of the operation `opcode` parsed as a number
+    fn number<T: FromStr>(&self, opcode: &str) -> Option<T> {
+        self.string(opcode)?.parse().ok()
+    }
+
+    /// Operands of the operation `opcode` parsed as moves in standard algebraic notation
+    fn moves(&self, opcode: &str) -> Result<Vec<AmbiguousMove>, ChessError> {
+        self.operation(opcode)
+            .unwrap_or_default()
+            .iter()
+            .map(|operand| match parser::pgn::chess_move(operand) {
+                Ok(("", chess_move)) => Ok(chess_move),
+                _ => Err(ChessError::InvalidEPD(operand.clone())),
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for Epd {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_epd_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::bitboard::BitBoard;
+    use crate::traits::LegalMoveGenerator as _;
+
+    const WAC_001: &str = "2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - bm Qg6; id \"WAC.001\";";
+
+    #[test]
+    fn typed_operations() {
+        let epd = Epd::from_epd_str(WAC_001).unwrap();
+        assert_eq!(epd.id(), Some("WAC.001"));
+        assert_eq!(epd.best_moves().unwrap(), [AmbiguousMove::try_from("Qg6").unwrap()]);
+        assert!(epd.avoid_moves().unwrap().is_empty());
+        assert_eq!((epd.acd(), epd.ce(), epd.comment(0)), (None, None, None));
+        assert_eq!(epd.as_epd_str(), WAC_001);
+
+        let board: BitBoard = epd.board().unwrap();
+        board.disambiguate_move(epd.best_moves().unwrap()[0]).unwrap();
+    }
+
+    #[test]
+    fn write_operations() {
+        let mut epd = Epd::from_fen_str("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").unwrap();
+        epd.set_best_moves(&[
+            AmbiguousMove::try_from("e4").unwrap(),
+            AmbiguousMove::try_from("d4").unwrap(),
+        ]);
+        epd.set_acd(20);
+        epd.set_ce(-15);
+        epd.set_comment(0, "King's pawn; or queen's");
+        epd.set_id("start");
+        epd.set_operation("hmvc", vec!["3".to_string()]);
+        let s = epd.as_epd_str();
+        assert_eq!(
+            s,
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - bm e4 d4; acd 20; ce -15; \
+             c0 \"King's pawn; or queen's\"; id \"start\"; hmvc 3;"
+        );
+        let parsed = Epd::from_epd_str(&s).unwrap();
+        assert_eq!(parsed, epd);
+        assert_eq!((parsed.acd(), parsed.ce()), (Some(20), Some(-15)));
+        assert_eq!(parsed.comment(0), Some("King's pawn; or queen's"));
+        assert_eq!(
+            parsed.fen_str(),
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 3 1"
+        );
+
+        epd.set_id("renamed");
+        assert_eq!(epd.remove_operation("bm").unwrap().len(), 2);
+        assert_eq!(epd.operations().len(), 5);
+        assert_eq!(epd.id(), Some("renamed"));
+    }
+
+    #[test]
+    fn invalid_records() {
+        Epd::from_epd_str("2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - bm Qg6").unwrap_err();
+        Epd::from_epd_str("2rr3k/pp3pp1/1nnqbN1p w - - bm Qg6;").unwrap_err();
+        let epd = Epd::from_epd_str("8/8/8/8/8/8/8/K6k w - - bm Kb9;").unwrap();
+        assert!(matches!(epd.best_moves(), Err(ChessError::InvalidEPD(_))));
+    }
+}
@@ -0,0 +1,789 @@
+//! Forsyth-Edwards Notation
+//!
+//! A [`Fen`] describes a position: where every piece is, whose turn it is, the castling rights, the
+//! en passant square and the move counters. Every board can be made from one, as
+//! [`crate::traits::ChessBoard`] requires `From<Fen>`, and gives one back with
+//! [`crate::traits::ChessBoard::to_fen`], so it is also how positions are moved between board
+//! representations.
+//!
+//! Positions are parsed with [`str::parse`] and written with [`ToString::to_string`], and can be
+//! made a piece at a time with a [`FenBuilder`].
+//!
+//! Castling rights record the file of the rook each one castles with, so Chess960 positions can be
+//! described. Both X-FEN, where `KQkq` refer to the outermost rooks and a file letter is only given
+//! for an inner rook, and Shredder-FEN, where every right is given by its rook file as in `HAha`,
+//! are read. Positions are written in X-FEN, which is standard FEN for standard chess, or in
+//! Shredder-FEN with [`Fen::to_shredder_string`].
+//!
+//! Crazyhouse positions give the pieces each side has in hand in brackets after the layout, as in
+//! `[Qp]`, and mark pieces promoted from pawns with a `~` after them. Three-check positions give
+//! the number of checks white and black have given after the move counters, as in `+1+0`.
+
+use core::str::FromStr;
+use core::{fmt, iter};
+
+#[cfg(test)]
+use proptest::prelude::Strategy;
+
+use crate::board::bitboard::BitBoard;
+use crate::enums::{CastlingSide, PieceColour, PieceKind};
+use crate::error::{ChessError, PositionError};
+use crate::parser;
+use crate::simple_types::{SimplePiece, SimpleSquare};
+use crate::traits::{ChessPiece as _, ChessSquare as _};
+
+/// Position in Forsyth-Edwards Notation
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Fen {
+    /// Pieces on each square, from the 8th rank to the 1st and the a-file to the h-file
+    pub(crate) layout: Box<[[Option<SimplePiece>; 8]; 8]>,
+    /// Number of each kind of piece each side has in hand in Crazyhouse, indexed by colour then
+    /// kind, if the position has pockets
+    pub(crate) pockets: Option<[[u8; 6]; 2]>,
+    /// Squares of pieces promoted from pawns in Crazyhouse, as bits indexed by rank * 8 + file
+    pub(crate) promoted: u64,
+    /// Number of checks each side has given in Three-check, indexed by colour, if the position
+    /// counts them
+    pub(crate) checks: Option<[u8; 2]>,
+    pub(crate) turn: PieceColour,
+    /// Rook files of the white kingside, white queenside, black kingside and black queenside
+    /// castling rights, if held
+    pub(crate) castling_rights: [Option<u8>; 4],
+    pub(crate) en_passant: Option<SimpleSquare>,
+    pub(crate) halfmove_clock: u32,
+    pub(crate) fullmove_number: u32,
+}
+
+impl Fen {
+    /// Builder for a position, starting from an empty board
+    pub fn builder() -> FenBuilder {
+        FenBuilder::new()
+    }
+
+    /// Starting position of Chess960 number `id`
+    ///
+    /// Positions are numbered from 0 to 959 as by Scharnagl, from `BBQNNRKR` to `RKRNNQBB`, with
+    /// 518 the standard starting position. The castling rights are for the rooks either side of the
+    /// king.
+    ///
+    /// # Panics
+    /// Panics if `id` > 959
+    pub fn chess960(id: u16) -> Self {
+        /// Indices among the five squares left of the two knights for each remaining number
+        const KNIGHTS: [(usize, usize); 10] = [
+            (0, 1),
+            (0, 2),
+            (0, 3),
+            (0, 4),
+            (1, 2),
+            (1, 3),
+            (1, 4),
+            (2, 3),
+            (2, 4),
+            (3, 4),
+        ];
+        assert!(id < 960, "Chess960 positions are numbered 0-959 inclusive, {id} > 959");
+        let mut back_rank = [None; 8];
+        let place = |back_rank: &mut [Option<PieceKind>; 8], n: usize, kind| {
+            let file = (0..8).filter(|&file| back_rank[file].is_none()).nth(n).unwrap();
+            back_rank[file] = Some(kind);
+        };
+        let id = usize::from(id);
+        back_rank[id % 4 * 2 + 1] = Some(PieceKind::Bishop);
+        back_rank[id / 4 % 4 * 2] = Some(PieceKind::Bishop);
+        place(&mut back_rank, id / 16 % 6, PieceKind::Queen);
+        let (first, second) = KNIGHTS[id / 96];
+        place(&mut back_rank, second, PieceKind::Knight);
+        place(&mut back_rank, first, PieceKind::Knight);
+        for kind in [PieceKind::Rook, PieceKind::King, PieceKind::Rook] {
+            place(&mut back_rank, 0, kind);
+        }
+
+        let rook_files: Vec<u8> = (0..8)
+            .filter(|&file| back_rank[file as usize] == Some(PieceKind::Rook))
+            .collect();
+        let mut builder = FenBuilder::new();
+        for (file, kind) in (0..8).zip(back_rank) {
+            for (colour, rank, pawn_rank) in [(PieceColour::White, 0, 1), (PieceColour::Black, 7, 6)] {
+                builder = builder
+                    .piece(
+                        SimpleSquare::new(file, rank),
+                        kind.map(|kind| SimplePiece::new(kind, colour)),
+                    )
+                    .piece(
+                        SimpleSquare::new(file, pawn_rank),
+                        Some(SimplePiece::new(PieceKind::Pawn, colour)),
+                    );
+            }
+        }
+        for colour in [PieceColour::White, PieceColour::Black] {
+            builder = builder
+                .castling_rook_file(colour, CastlingSide::QueenSide, Some(rook_files[0]))
+                .castling_rook_file(colour, CastlingSide::KingSide, Some(rook_files[1]));
+        }
+        builder.build_unchecked()
+    }
+
+    /// Piece on `square`, if there is one
+    pub fn piece(&self, square: SimpleSquare) -> Option<SimplePiece> {
+        self.layout[7 - square.rank() as usize][square.file() as usize]
+    }
+
+    /// Number of pieces of `kind` that `colour` has in hand in Crazyhouse, if the position has
+    /// pockets
+    pub fn pocket(&self, colour: PieceColour, kind: PieceKind) -> Option<u8> {
+        self.pockets.map(|pockets| pockets[colour as usize][kind as usize])
+    }
+
+    /// Whether the piece on `square` was promoted from a pawn, so is taken back into the pocket as
+    /// a pawn in Crazyhouse
+    pub fn is_promoted(&self, square: SimpleSquare) -> bool {
+        self.promoted & (1 << (square.rank() * 8 + square.file())) != 0
+    }
+
+    /// Number of checks `colour` has given in Three-check, if the position counts them
+    pub fn checks(&self, colour: PieceColour) -> Option<u8> {
+        self.checks.map(|checks| checks[colour as usize])
+    }
+
+    /// Colour of the side to move
+    pub fn turn(&self) -> PieceColour {
+        self.turn
+    }
+
+    /// Whether `colour` may still castle on `side`
+    pub fn castling_right(&self, colour: PieceColour, side: CastlingSide) -> bool {
+        self.castling_rook_file(colour, side).is_some()
+    }
+
+    /// File of the rook `colour` may still castle with on `side`, if it may
+    pub fn castling_rook_file(&self, colour: PieceColour, side: CastlingSide) -> Option<u8> {
+        self.castling_rights[castling_index(colour, side)]
+    }
+
+    /// Square a pawn that just moved two squares passed over, if any
+    pub fn en_passant(&self) -> Option<SimpleSquare> {
+        self.en_passant
+    }
+
+    /// Number of halfmoves since the last capture or pawn move
+    pub fn halfmove_clock(&self) -> u32 {
+        self.halfmove_clock
+    }
+
+    /// Number of the current move, starting at 1 and going up after black moves
+    pub fn fullmove_number(&self) -> u32 {
+        self.fullmove_number
+    }
+
+    /// Check the position could be reached in a game of chess
+    ///
+    /// Each side must have one king, there must be no pawns on the first or last rank, the side not
+    /// to move must not be in check, castling rights need the king on the back rank with the rook
+    /// on its file and side of the king, and the en passant square must be behind a pawn of the
+    /// side that just moved with both it and the square the pawn came from empty.
+    ///
+    /// # Errors
+    /// - [`crate::error::ChessError::InvalidPosition`] with the first rule the position breaks
+    pub fn validate(&self) -> Result<(), ChessError> {
+        for colour in [PieceColour::White, PieceColour::Black] {
+            let king = SimplePiece::new(PieceKind::King, colour);
+            let count = self
+                .layout
+                .iter()
+                .flatten()
+                .filter(|piece| **piece == Some(king))
+                .count();
+            if count != 1 {
+                return Err(PositionError::KingCount { colour, count }.into());
+            }
+        }
+        for rank in [0, 7] {
+            for file in 0..8 {
+                let square = SimpleSquare::new(file, rank);
+                if self.piece(square).is_some_and(|piece| piece.kind() == PieceKind::Pawn) {
+                    return Err(PositionError::PawnOnBackRank(square).into());
+                }
+            }
+        }
+        for colour in [PieceColour::White, PieceColour::Black] {
+            let back_rank = match colour {
+                PieceColour::White => 0,
+                PieceColour::Black => 7,
+            };
+            let has =
+                |file, kind| self.piece(SimpleSquare::new(file, back_rank)) == Some(SimplePiece::new(kind, colour));
+            let king_file = (0..8).find(|file| has(*file, PieceKind::King));
+            for side in [CastlingSide::KingSide, CastlingSide::QueenSide] {
+                let Some(rook_file) = self.castling_rook_file(colour, side) else {
+                    continue;
+                };
+                let rook_side = king_file.is_some_and(|king_file| match side {
+                    CastlingSide::KingSide => rook_file > king_file,
+                    CastlingSide::QueenSide => rook_file < king_file,
+                });
+                if !(rook_side && has(rook_file, PieceKind::Rook)) {
+                    return Err(PositionError::CastlingWithoutPieces { colour, side }.into());
+                }
+            }
+        }
+        if let Some(square) = self.en_passant {
+            let (rank, pawn_rank, from_rank) = match self.turn {
+                PieceColour::White => (5, 4, 6),
+                PieceColour::Black => (2, 3, 1),
+            };
+            let pawn = self.piece(SimpleSquare::new(square.file(), pawn_rank));
+            if square.rank() != rank
+                || pawn.is_none_or(|pawn| pawn.kind() != PieceKind::Pawn || pawn.colour() == self.turn)
+                || self.piece(square).is_some()
+                || self.piece(SimpleSquare::new(square.file(), from_rank)).is_some()
+            {
+                return Err(PositionError::ImpossibleEnPassant(square).into());
+            }
+        }
+        if BitBoard::from(self.clone()).king_in_check(!self.turn)? {
+            return Err(PositionError::OpponentInCheck(!self.turn).into());
+        }
+        Ok(())
+    }
+
+    /// First four fields giving the position without its move counters, as used by EPD
+    pub fn position_str(&self) -> String {
+        self.fields_str(false)
+    }
+
+    /// Return as string in Shredder-FEN, giving every castling right by its rook file
+    pub fn to_shredder_string(&self) -> String {
+        format!(
+            "{} {} {}{}",
+            self.fields_str(true),
+            self.halfmove_clock,
+            self.fullmove_number,
+            self.checks_str()
+        )
+    }
+
+    /// Checks given by each side as ` +N+M`, or nothing if the position doesn't count them
+    fn checks_str(&self) -> String {
+        self.checks
+            .map(|checks| {
+                format!(
+                    " +{}+{}",
+                    checks[PieceColour::White as usize],
+                    checks[PieceColour::Black as usize]
+                )
+            })
+            .unwrap_or_default()
+    }
+
+    /// First four fields, with castling rights in Shredder-FEN if `shredder` or X-FEN if not
+    fn fields_str(&self, shredder: bool) -> String {
+        const CASTLING_LETTERS: [char; 4] = ['K', 'Q', 'k', 'q'];
+        const POCKET_ORDER: [PieceKind; 5] = [
+            PieceKind::Queen,
+            PieceKind::Rook,
+            PieceKind::Bishop,
+            PieceKind::Knight,
+            PieceKind::Pawn,
+        ];
+        let mut s = parser::fen::layout_to_str(&self.layout, self.promoted);
+        if let Some(pockets) = self.pockets {
+            s.push('[');
+            for colour in [PieceColour::White, PieceColour::Black] {
+                for kind in POCKET_ORDER {
+                    let piece = SimplePiece::new(kind, colour).as_fen();
+                    s.extend(iter::repeat_n(piece, pockets[colour as usize][kind as usize].into()));
+                }
+            }
+            s.push(']');
+        }
+        s.push(' ');
+        match self.turn {
+            PieceColour::Black => s.push('b'),
+            PieceColour::White => s.push('w'),
+        }
+        s.push(' ');
+        for (colour, offset) in [(PieceColour::White, 0), (PieceColour::Black, 2)] {
+            for (side, i) in [(CastlingSide::KingSide, offset), (CastlingSide::QueenSide, offset + 1)] {
+                let Some(file) = self.castling_rights[i] else {
+                    continue;
+                };
+                if !shredder && file == parser::fen::outer_rook_file(&self.layout, colour, side) {
+                    s.push(CASTLING_LETTERS[i]);
+                } else {
+                    let letter = char::from(b'A' + file);
+                    match colour {
+                        PieceColour::White => s.push(letter),
+                        PieceColour::Black => s.push(letter.to_ascii_lowercase()),
+                    }
+                }
+            }
+        }
+        if self.castling_rights.iter().all(Option::is_none) {
+            s.push('-');
+        }
+        s.push(' ');
+        if let Some(square) = self.en_passant {
+            s.push_str(&square.to_string());
+        } else {
+            s.push('-');
+        }
+        s
+    }
+
+    /// Strategy for any layout and fields, not necessarily a legal position
+    #[cfg(test)]
+    pub fn strategy() -> impl Strategy<Value = Self> {
+        use core::array;
+        use proptest::{
+            array::{uniform2, uniform4},
+            collection::vec,
+            option::of,
+            prelude::any,
+        };
+
+        let layout = vec(of(SimplePiece::strategy()), 64);
+        let turn = PieceColour::strategy();
+        let castling_rights = uniform4(any::<bool>());
+        let en_passant = of(SimpleSquare::strategy());
+        let halfmove_clock = any::<u32>();
+        let fullmove_number = any::<u32>();
+        let checks = of(uniform2(any::<u8>()));
+        (
+            layout,
+            turn,
+            castling_rights,
+            en_passant,
+            halfmove_clock,
+            fullmove_number,
+            checks,
+        )
+            .prop_map(
+                |(pieces, turn, castling_rights, en_passant, halfmove_clock, fullmove_number, checks)| {
+                    let mut layout = Box::new([[None; 8]; 8]);
+                    for (i, piece) in pieces.into_iter().enumerate() {
+                        layout[i / 8][i % 8] = piece;
+                    }
+                    let sides = [
+                        (PieceColour::White, CastlingSide::KingSide),
+                        (PieceColour::White, CastlingSide::QueenSide),
+                        (PieceColour::Black, CastlingSide::KingSide),
+                        (PieceColour::Black, CastlingSide::QueenSide),
+                    ];
+                    // Only rights with a rook file on the right side of the king can be read back
+                    let castling_rights = array::from_fn(|i| {
+                        let (colour, side) = sides[i];
+                        let king_file = parser::fen::king_file(&layout, colour);
+                        let file = parser::fen::outer_rook_file(&layout, colour, side);
+                        let rook_side = match side {
+                            CastlingSide::KingSide => file > king_file,
+                            CastlingSide::QueenSide => file < king_file,
+                        };
+                        (castling_rights[i] && rook_side).then_some(file)
+                    });
+                    Self {
+                        layout,
+                        pockets: None,
+                        promoted: 0,
+                        checks,
+                        turn,
+                        castling_rights,
+                        en_passant,
+                        halfmove_clock,
+                        fullmove_number,
+                    }
+                },
+            )
+    }
+}
+
+impl fmt::Display for Fen {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {}{}",
+            self.position_str(),
+            self.halfmove_clock,
+            self.fullmove_number,
+            self.checks_str()
+        )
+    }
+}
+
+impl FromStr for Fen {
+    type Err = ChessError;
+
+    /// Parse a FEN, with the move counters defaulting to 0 if not given
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match parser::fen::fen(s) {
+            Ok((rest, fen)) if rest.trim().is_empty() => Ok(fen),
+            _ => Err(ChessError::InvalidFEN(s.to_string())),
+        }
+    }
+}
+
+/// Index of a castling right in the castling rights of a [`Fen`]
+pub(crate) const fn castling_index(colour: PieceColour, side: CastlingSide) -> usize {
+    let offset = match colour {
+        PieceColour::White => 0,
+        PieceColour::Black => 2,
+    };
+    match side {
+        CastlingSide::KingSide => offset,
+        CastlingSide::QueenSide => offset + 1,
+    }
+}
+
+/// Builder for a [`Fen`], checking the position is legal when built
+///
+/// Starts from an empty board with white to move, no castling rights, no en passant square, and
+/// the move counters at 0 and 1.
+#[derive(Debug, Clone)]
+pub struct FenBuilder {
+    fen: Fen,
+}
+
+impl FenBuilder {
+    /// Builder starting from an empty board
+    pub fn new() -> Self {
+        Self {
+            fen: Fen {
+                layout: Box::new([[None; 8]; 8]),
+                pockets: None,
+                promoted: 0,
+                checks: None,
+                turn: PieceColour::White,
+                castling_rights: [None; 4],
+                en_passant: None,
+                halfmove_clock: 0,
+                fullmove_number: 1,
+            },
+        }
+    }
+
+    /// Put `piece` on `square`, or empty it if `None`
+    #[must_use]
+    pub fn piece(mut self, square: SimpleSquare, piece: Option<SimplePiece>) -> Self {
+        self.fen.layout[7 - square.rank() as usize][square.file() as usize] = piece;
+        self
+    }
+
+    /// Set the number of pieces of `kind` that `colour` has in hand in Crazyhouse, giving the
+    /// position pockets if it didn't have them
+    ///
+    /// # Panics
+    /// Panics if `kind` is [`PieceKind::King`]
+    #[must_use]
+    pub fn pocket(mut self, colour: PieceColour, kind: PieceKind, count: u8) -> Self {
+        assert!(kind != PieceKind::King, "Kings can't be held in a pocket");
+        self.fen.pockets.get_or_insert_default()[colour as usize][kind as usize] = count;
+        self
+    }
+
+    /// Set whether the piece on `square` was promoted from a pawn, for Crazyhouse
+    #[must_use]
+    pub fn promoted(mut self, square: SimpleSquare, promoted: bool) -> Self {
+        let bit = 1 << (square.rank() * 8 + square.file());
+        if promoted {
+            self.fen.promoted |= bit;
+        } else {
+            self.fen.promoted &= !bit;
+        }
+        self
+    }
+
+    /// Set the number of checks `colour` has given in Three-check, counting checks for both sides
+    /// if the position didn't
+    #[must_use]
+    pub fn checks(mut self, colour: PieceColour, count: u8) -> Self {
+        self.fen.checks.get_or_insert_default()[colour as usize] = count;
+        self
+    }
+
+    /// Set the colour of the side to move
+    #[must_use]
+    pub fn turn(mut self, turn: PieceColour) -> Self {
+        self.fen.turn = turn;
+        self
+    }
+
+    /// Set whether `colour` may still castle on `side` with the rook on the h or a-file
+    #[must_use]
+    pub fn castling_right(self, colour: PieceColour, side: CastlingSide, allowed: bool) -> Self {
+        let file = match side {
+            CastlingSide::KingSide => 7,
+            CastlingSide::QueenSide => 0,
+        };
+        self.castling_rook_file(colour, side, allowed.then_some(file))
+    }
+
+    /// Set the file of the rook `colour` may still castle with on `side`, or `None` if it may not
+    ///
+    /// # Panics
+    /// Panics if `file` > 7
+    #[must_use]
+    pub fn castling_rook_file(mut self, colour: PieceColour, side: CastlingSide, file: Option<u8>) -> Self {
+        assert!(file.is_none_or(|file| file < 8), "File must be between 0-7 inclusive");
+        self.fen.castling_rights[castling_index(colour, side)] = file;
+        self
+    }
+
+    /// Set the square a pawn that just moved two squares passed over
+    #[must_use]
+    pub fn en_passant(mut self, square: Option<SimpleSquare>) -> Self {
+        self.fen.en_passant = square;
+        self
+    }
+
+    /// Set the number of halfmoves since the last capture or pawn move
+    #[must_use]
+    pub fn halfmove_clock(mut self, halfmove_clock: u32) -> Self {
+        self.fen.halfmove_clock = halfmove_clock;
+        self
+    }
+
+    /// Set the number of the current move
+    #[must_use]
+    pub fn fullmove_number(mut self, fullmove_number: u32) -> Self {
+        self.fen.fullmove_number = fullmove_number;
+        self
+    }
+
+    /// Check and return the position
+    ///
+    /// # Errors
+    /// - [`crate::error::ChessError::InvalidPosition`] if the position breaks a rule checked by
+    ///   [`Fen::validate`]
+    /// - [`crate::error::ChessError::InvalidFEN`] if the fullmove number is 0
+    pub fn build(self) -> Result<Fen, ChessError> {
+        self.fen.validate()?;
+        if self.fen.fullmove_number == 0 {
+            return Err(ChessError::InvalidFEN(format!(
+                "{}, fullmove number must start at 1",
+                self.fen
+            )));
+        }
+        Ok(self.fen)
+    }
+
+    /// Return the position without checking it, for setups that aren't legal chess positions
+    pub fn build_unchecked(self) -> Fen {
+        self.fen
+    }
+}
+
+impl Default for FenBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<Fen> for FenBuilder {
+    /// Builder starting from the position of `fen`
+    fn from(fen: Fen) -> Self {
+        Self { fen }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::proptest;
+
+    const START: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+    fn square(s: &str) -> SimpleSquare {
+        SimpleSquare::from_pgn_str(s).unwrap()
+    }
+
+    #[test]
+    fn getters() {
+        let fen: Fen = "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQq f6 0 3"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            fen.piece(square("e5")),
+            Some(SimplePiece::new(PieceKind::Pawn, PieceColour::White))
+        );
+        assert_eq!(fen.piece(square("e4")), None);
+        assert_eq!(fen.turn(), PieceColour::White);
+        assert!(fen.castling_right(PieceColour::White, CastlingSide::KingSide));
+        assert!(!fen.castling_right(PieceColour::Black, CastlingSide::KingSide));
+        assert_eq!(fen.en_passant(), Some(square("f6")));
+        assert_eq!((fen.halfmove_clock(), fen.fullmove_number()), (0, 3));
+        assert_eq!(fen.checks(PieceColour::White), None);
+        "rnbqkbnr/pppppppp/8 w KQkq - 0 1".parse::<Fen>().unwrap_err();
+        format!("{START} trailing").parse::<Fen>().unwrap_err();
+    }
+
+    #[test]
+    fn build_position() {
+        let white = |kind| Some(SimplePiece::new(kind, PieceColour::White));
+        let black = |kind| Some(SimplePiece::new(kind, PieceColour::Black));
+        let fen = Fen::builder()
+            .piece(square("e1"), white(PieceKind::King))
+            .piece(square("h1"), white(PieceKind::Rook))
+            .piece(square("e8"), black(PieceKind::King))
+            .piece(square("d4"), black(PieceKind::Pawn))
+            .piece(square("e4"), white(PieceKind::Pawn))
+            .turn(PieceColour::Black)
+            .castling_right(PieceColour::White, CastlingSide::KingSide, true)
+            .en_passant(Some(square("e3")))
+            .fullmove_number(30)
+            .build()
+            .unwrap();
+        assert_eq!(fen.to_string(), "4k3/8/8/8/3pP3/8/8/4K2R b K e3 0 30");
+
+        let builder = FenBuilder::from(fen);
+        builder
+            .clone()
+            .castling_right(PieceColour::White, CastlingSide::QueenSide, true)
+            .build()
+            .unwrap_err();
+        builder.clone().en_passant(Some(square("d3"))).build().unwrap_err();
+        builder.clone().turn(PieceColour::White).build().unwrap_err();
+        builder.clone().fullmove_number(0).build().unwrap_err();
+        builder.clone().piece(square("e4"), None).build().unwrap_err();
+        assert_eq!(
+            builder
+                .clone()
+                .checks(PieceColour::Black, 2)
+                .build()
+                .unwrap()
+                .to_string(),
+            "4k3/8/8/8/3pP3/8/8/4K2R b K e3 0 30 +0+2"
+        );
+        let fairy = builder.piece(square("e8"), None).build_unchecked();
+        assert_eq!(fairy.to_string(), "8/8/8/8/3pP3/8/8/4K2R b K e3 0 30");
+    }
+
+    #[test]
+    fn build_starting_position() {
+        let start: Fen = START.parse().unwrap();
+        let mut builder = Fen::builder();
+        for rank in 0..8 {
+            for file in 0..8 {
+                let square = SimpleSquare::new(file, rank);
+                builder = builder.piece(square, start.piece(square));
+            }
+        }
+        for colour in [PieceColour::White, PieceColour::Black] {
+            for side in [CastlingSide::KingSide, CastlingSide::QueenSide] {
+                builder = builder.castling_right(colour, side, true);
+            }
+        }
+        assert_eq!(builder.build().unwrap(), start);
+    }
+
+    #[test]
+    fn chess960_castling() {
+        let fen: Fen = "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9"
+            .parse()
+            .unwrap();
+        fen.validate().unwrap();
+        assert_eq!(
+            fen.castling_rook_file(PieceColour::White, CastlingSide::QueenSide),
+            Some(5)
+        );
+        assert_eq!(
+            fen.to_string(),
+            "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w KQkq - 2 9"
+        );
+        assert_eq!(
+            fen.to_shredder_string(),
+            "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9"
+        );
+
+        let inner = Fen::builder()
+            .piece(
+                square("g1"),
+                Some(SimplePiece::new(PieceKind::King, PieceColour::White)),
+            )
+            .piece(
+                square("d1"),
+                Some(SimplePiece::new(PieceKind::Rook, PieceColour::White)),
+            )
+            .piece(
+                square("f1"),
+                Some(SimplePiece::new(PieceKind::Rook, PieceColour::White)),
+            )
+            .piece(
+                square("b8"),
+                Some(SimplePiece::new(PieceKind::King, PieceColour::Black)),
+            )
+            .castling_rook_file(PieceColour::White, CastlingSide::QueenSide, Some(5))
+            .build()
+            .unwrap();
+        assert_eq!(inner.to_string(), "1k6/8/8/8/8/8/8/3R1RK1 w F - 0 1");
+        assert_eq!(inner.to_string().parse::<Fen>().unwrap(), inner);
+        FenBuilder::from(inner)
+            .castling_rook_file(PieceColour::White, CastlingSide::KingSide, Some(5))
+            .build()
+            .unwrap_err();
+    }
+
+    #[test]
+    fn validate() {
+        let error = |fen: &str| match fen.parse::<Fen>().unwrap().validate() {
+            Err(ChessError::InvalidPosition(error)) => error,
+            other => panic!("{fen} gave {other:?}"),
+        };
+        START.parse::<Fen>().unwrap().validate().unwrap();
+        assert_eq!(
+            error("4k3/8/8/8/8/8/8/8 w - - 0 1"),
+            PositionError::KingCount {
+                colour: PieceColour::White,
+                count: 0
+            }
+        );
+        assert_eq!(
+            error("4k3/8/8/8/8/8/8/3KK3 w - - 0 1"),
+            PositionError::KingCount {
+                colour: PieceColour::White,
+                count: 2
+            }
+        );
+        assert_eq!(
+            error("4k3/8/8/8/8/8/8/4K2p b - - 0 1"),
+            PositionError::PawnOnBackRank(square("h1"))
+        );
+        assert_eq!(
+            error("4k3/8/8/8/8/8/8/4K3 w k - 0 1"),
+            PositionError::CastlingWithoutPieces {
+                colour: PieceColour::Black,
+                side: CastlingSide::KingSide
+            }
+        );
+        assert_eq!(
+            error("4k3/8/8/8/4P3/8/8/4K3 w - e3 0 1"),
+            PositionError::ImpossibleEnPassant(square("e3"))
+        );
+        assert_eq!(
+            error("4k2R/8/8/8/8/8/8/4K3 w - - 0 1"),
+            PositionError::OpponentInCheck(PieceColour::Black)
+        );
+    }
+
+    #[test]
+    fn chess960_positions() {
+        assert_eq!(Fen::chess960(518).to_string(), START);
+        assert_eq!(
+            Fen::chess960(0).to_shredder_string(),
+            "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w HFhf - 0 1"
+        );
+        assert_eq!(
+            Fen::chess960(959).to_string(),
+            "rkrnnqbb/pppppppp/8/8/8/8/PPPPPPPP/RKRNNQBB w KQkq - 0 1"
+        );
+        for id in 0..960 {
+            Fen::chess960(id).validate().unwrap();
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn display_round_trip(f in Fen::strategy()) {
+            assert_eq!(f.to_string().parse::<Fen>().unwrap(), f);
+        }
+    }
+}
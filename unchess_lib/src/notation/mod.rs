@@ -1,5 +1,10 @@
 //! Module for game notations like PGN and FEN
 
+pub mod epd;
+pub mod fen;
+pub mod pgn;
+pub mod replay;
+
 use crate::{enums::AmbiguousMove, error::ChessError, parser};
 
 /// Convert u8 representation of file into char based on pgn standard
@@ -74,14 +79,18 @@ pub fn char_to_rank(rank: char) -> Result<u8, ChessError> {
     }
 }
 
-/// Convert pgn file to vector of moves
+/// Convert pgn file to vector of the moves in its main line
+///
+/// Use [`pgn::PgnGame`] to keep the tags, result and annotations as well, including the `FEN` tag
+/// giving the position the moves start from.
 ///
 /// # Errors
-/// - [`crate::error::ChessError::InvalidPGN`] if PGN can't be parsed
+/// - [`crate::error::ChessError::InvalidPGN`] if PGN can't be parsed or has no moves
 pub fn pgn_to_moves(input: &str) -> Result<Vec<AmbiguousMove>, ChessError> {
-    if let Ok((_, (_, moves))) = parser::pgn::pgn(input) {
-        Ok(moves)
-    } else {
-        Err(ChessError::InvalidPGN(input.to_string()))
+    match parser::pgn::pgn(input) {
+        Ok((_, (_, mainline))) if !mainline.moves.is_empty() => {
+            Ok(mainline.moves.into_iter().map(|pgn_move| pgn_move.chess_move).collect())
+        }
+        _ => Err(ChessError::InvalidPGN(input.to_string())),
     }
 }
@@ -0,0 +1,979 @@
+//! Portable Game Notation game model
+//!
+//! A [`PgnGame`] keeps everything in a PGN game apart from formatting: the tag pairs in their
+//! original order, the moves of the main line with their comments, numeric annotation glyphs and
+//! recursive annotation variations, and the game termination marker.
+//!
+//! Games are written in the PGN export format: the Seven Tag Roster in order followed by any other
+//! tags sorted by name, then the movetext with every move numbered where needed, glyphs written as
+//! `$n`, comments in braces and lines wrapped to fit in 80 columns. Parsing a game written this way
+//! and writing it again gives exactly the same text.
+//!
+//! Games starting from a position other than the standard one give it with the `SetUp` and `FEN`
+//! tags, which are used to number the moves and by [`PgnGame::starting_board`].
+//!
+//! Files with many games can be read one game at a time with [`PgnReader`].
+//!
+//! Games are parsed strictly by default, following the PGN import format. Lenient parsing also
+//! accepts some common departures from it, like castling written with zeros, giving a
+//! [`PgnWarning`] for each.
+
+use core::fmt;
+use std::io::{self, BufRead};
+
+use crate::enums::{AmbiguousMove, GameOutcome, PieceColour};
+use crate::error::ChessError;
+use crate::parser;
+use crate::traits::{ChessBoard, LegalMoveGenerator};
+
+/// Tags of the Seven Tag Roster, in the order they should appear in a game
+pub const SEVEN_TAG_ROSTER: [&str; 7] = ["Event", "Site", "Date", "Round", "White", "Black", "Result"];
+
+/// Longest line of movetext written in export format, so lines fit in 80 columns
+pub const EXPORT_LINE_LENGTH: usize = 79;
+
+/// Game termination marker
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum GameResult {
+    /// White won, `1-0`
+    WhiteWins,
+    /// Black won, `0-1`
+    BlackWins,
+    /// Game drawn, `1/2-1/2`
+    Draw,
+    /// Game still in progress, abandoned or result otherwise unknown, `*`
+    #[default]
+    Unknown,
+}
+
+impl GameResult {
+    /// Return as string in PGN format
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GameResult::WhiteWins => "1-0",
+            GameResult::BlackWins => "0-1",
+            GameResult::Draw => "1/2-1/2",
+            GameResult::Unknown => "*",
+        }
+    }
+}
+
+impl fmt::Display for GameResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl TryFrom<&str> for GameResult {
+    type Error = ChessError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "1-0" => Ok(Self::WhiteWins),
+            "0-1" => Ok(Self::BlackWins),
+            "1/2-1/2" => Ok(Self::Draw),
+            "*" => Ok(Self::Unknown),
+            _ => Err(ChessError::InvalidPGN(value.to_string())),
+        }
+    }
+}
+
+/// Numeric annotation glyph, such as `$1` for a good move
+///
+/// The suffix annotations `!`, `?`, `!!`, `??`, `!?` and `?!` are stored as the glyphs `$1` to `$6`
+/// they stand for.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Nag(pub u8);
+
+impl Nag {
+    /// Glyph for a suffix annotation such as `!?`, if it is one
+    pub fn from_suffix(suffix: &str) -> Option<Self> {
+        match suffix {
+            "!" => Some(Self(1)),
+            "?" => Some(Self(2)),
+            "!!" => Some(Self(3)),
+            "??" => Some(Self(4)),
+            "!?" => Some(Self(5)),
+            "?!" => Some(Self(6)),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Nag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "${}", self.0)
+    }
+}
+
+/// Departure from the PGN standard accepted when parsing leniently
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PgnWarningKind {
+    /// Castling written with zeros, like `0-0`
+    ZeroCastling,
+    /// En passant capture followed by `e.p.`, which is removed
+    EnPassantSuffix,
+    /// Null move `--`, which is removed along with the rest of the line it is in
+    NullMove {
+        /// Moves in the rest of the line and any variations in it, in the order they were written
+        dropped: Vec<String>,
+    },
+    /// Line starting with `%`, which is ignored
+    EscapeLine,
+    /// Figurine piece symbol like `♘` in place of a letter
+    Figurine,
+    /// Game without a game termination marker, so the result is unknown
+    MissingResult,
+}
+
+impl fmt::Display for PgnWarningKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let description = match self {
+            PgnWarningKind::ZeroCastling => "castling written with zeros",
+            PgnWarningKind::EnPassantSuffix => "en passant suffix removed",
+            PgnWarningKind::NullMove { dropped } if dropped.is_empty() => "null move removed",
+            PgnWarningKind::NullMove { dropped } => {
+                return write!(f, "null move removed along with {}", dropped.join(" "));
+            }
+            PgnWarningKind::EscapeLine => "escape line ignored",
+            PgnWarningKind::Figurine => "figurine piece symbol",
+            PgnWarningKind::MissingResult => "missing game termination marker",
+        };
+        write!(f, "{description}")
+    }
+}
+
+/// Departure from the PGN standard along with where it was found
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PgnWarning {
+    /// Byte offset from the start of the input
+    pub offset: u64,
+    /// Line number, starting from 1
+    pub line: u64,
+    /// What was found
+    pub kind: PgnWarningKind,
+}
+
+impl fmt::Display for PgnWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at byte {}, line {}", self.kind, self.offset, self.line)
+    }
+}
+
+/// Move in a game along with its annotations
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PgnMove {
+    /// The move in standard algebraic notation
+    pub chess_move: AmbiguousMove,
+    /// Numeric annotation glyphs following the move
+    pub nags: Vec<Nag>,
+    /// Comments following the move
+    pub comments: Vec<String>,
+    /// Alternatives to this move, each starting from the position before it
+    pub variations: Vec<Variation>,
+}
+
+impl PgnMove {
+    /// Move without any annotations
+    pub fn new(chess_move: AmbiguousMove) -> Self {
+        Self {
+            chess_move,
+            nags: vec![],
+            comments: vec![],
+            variations: vec![],
+        }
+    }
+}
+
+impl From<AmbiguousMove> for PgnMove {
+    fn from(value: AmbiguousMove) -> Self {
+        Self::new(value)
+    }
+}
+
+/// Line of moves, either the main line of a game or a recursive annotation variation
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Variation {
+    /// Comments before the first move of the line
+    pub comments: Vec<String>,
+    /// Moves of the line in order
+    pub moves: Vec<PgnMove>,
+}
+
+/// Game in Portable Game Notation
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct PgnGame {
+    /// Tag pairs as name and value, in the order they were given
+    pub tags: Vec<(String, String)>,
+    /// Main line of the game
+    pub mainline: Variation,
+    /// Game termination marker
+    pub result: GameResult,
+}
+
+impl PgnGame {
+    /// Game with no moves and the Seven Tag Roster filled in with unknown values
+    pub fn new() -> Self {
+        let tags = SEVEN_TAG_ROSTER
+            .iter()
+            .map(|&name| {
+                let value = match name {
+                    "Date" => "????.??.??",
+                    "Result" => "*",
+                    _ => "?",
+                };
+                (name.to_string(), value.to_string())
+            })
+            .collect();
+        Self {
+            tags,
+            ..Self::default()
+        }
+    }
+
+    /// Parse a single game from PGN
+    ///
+    /// Whitespace around the game is ignored, but anything else after the game termination marker
+    /// is an error.
+    ///
+    /// # Errors
+    /// - [`crate::error::ChessError::InvalidPGN`] if the game can't be parsed
+    pub fn from_pgn_str(input: &str) -> Result<Self, ChessError> {
+        match parse_game(input, false) {
+            Ok((game, _)) => Ok(game),
+            Err(_) => Err(ChessError::InvalidPGN(input.to_string())),
+        }
+    }
+
+    /// Parse a single game from PGN, accepting common departures from the standard
+    ///
+    /// Along with everything [`PgnGame::from_pgn_str`] accepts, castling written with zeros, `e.p.`
+    /// suffixes, null moves `--`, `%` escape lines, figurine piece symbols and a missing game
+    /// termination marker are accepted, giving a warning for each. Null moves can't be played so
+    /// are dropped, along with the rest of the line they are in, and their warning names every move
+    /// dropped.
+    ///
+    /// # Errors
+    /// - [`crate::error::ChessError::InvalidPGN`] if the game can't be parsed
+    pub fn from_pgn_str_lenient(input: &str) -> Result<(Self, Vec<PgnWarning>), ChessError> {
+        let Ok((game, warnings)) = parse_game(input, true) else {
+            return Err(ChessError::InvalidPGN(input.to_string()));
+        };
+        let warnings = warnings
+            .into_iter()
+            .map(|(offset, kind)| PgnWarning {
+                offset: offset as u64,
+                line: input[..offset].matches('\n').count() as u64 + 1,
+                kind,
+            })
+            .collect();
+        Ok((game, warnings))
+    }
+
+    /// Value of the tag `name`, if present
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag_name, _)| tag_name == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Set the tag `name` to `value`, replacing any value it already had
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        if let Some((_, old_value)) = self.tags.iter_mut().find(|(tag_name, _)| tag_name == name) {
+            *old_value = value.to_string();
+        } else {
+            self.tags.push((name.to_string(), value.to_string()));
+        }
+    }
+
+    /// Moves of the main line without annotations
+    pub fn mainline_moves(&self) -> impl Iterator<Item = AmbiguousMove> {
+        self.mainline.moves.iter().map(|pgn_move| pgn_move.chess_move)
+    }
+
+    /// FEN of the position the game starts from, if it isn't the standard starting position
+    ///
+    /// Given by the `FEN` tag, unless the `SetUp` tag is `0`.
+    pub fn starting_fen(&self) -> Option<&str> {
+        if self.tag("SetUp") == Some("0") {
+            None
+        } else {
+            self.tag("FEN")
+        }
+    }
+
+    /// Board in the position the game starts from
+    ///
+    /// # Errors
+    /// - [`crate::error::ChessError::InvalidFEN`] if the `FEN` tag isn't valid
+    /// - [`crate::error::ChessError::InvalidPosition`] if the position of the `FEN` tag is illegal
+    pub fn starting_board<B: ChessBoard>(&self) -> Result<B, ChessError> {
+        match self.starting_fen() {
+            Some(fen) => B::from_fen(fen),
+            None => Ok(B::starting_board()),
+        }
+    }
+
+    /// Board after playing every move of the main line from the starting position
+    ///
+    /// # Errors
+    /// - [`crate::error::ChessError::InvalidFEN`] if the `FEN` tag isn't valid
+    /// - [`crate::error::ChessError::InvalidPosition`] if the position of the `FEN` tag is illegal
+    /// - [`crate::error::ChessError::ImpossibleMove`] if a move doesn't match any legal move
+    /// - [`crate::error::ChessError::AmbiguousMove`] if a move matches more than one legal move
+    pub fn final_board<B: LegalMoveGenerator>(&self) -> Result<B, ChessError> {
+        let mut board = self.starting_board::<B>()?;
+        for chess_move in self.mainline_moves() {
+            let unamb_move = board.disambiguate_move(chess_move)?;
+            board.move_piece_legal(unamb_move)?;
+        }
+        Ok(board)
+    }
+
+    /// Check the game termination marker and any `Termination` tag against the final position
+    ///
+    /// `board` should be the position at the end of the main line, as given by
+    /// [`PgnGame::final_board`]. A game ended on the board by checkmate or an automatic draw must
+    /// have the matching result, and a `Termination` tag of `normal` if it has one. Any other game
+    /// may have been resigned, agreed drawn or lost on time so can have any result, except that
+    /// `normal` games must have a result and `unterminated` games must not.
+    ///
+    /// # Errors
+    /// - [`crate::error::ChessError::ResultMismatch`] if the result doesn't match the position
+    /// - [`crate::error::ChessError::TerminationMismatch`] if the `Termination` tag doesn't match
+    ///   the result or position
+    /// - [`crate::error::ChessError::InvalidPosition`] if there is not one king of each colour
+    pub fn check_result<B: LegalMoveGenerator>(&self, board: &B) -> Result<(), ChessError> {
+        let outcome = board.outcome()?;
+        let expected = match outcome {
+            GameOutcome::Win { winner, .. } => Some(match winner {
+                PieceColour::White => GameResult::WhiteWins,
+                PieceColour::Black => GameResult::BlackWins,
+            }),
+            GameOutcome::Draw(_) => Some(GameResult::Draw),
+            GameOutcome::Ongoing | GameOutcome::ClaimableDraw(_) => None,
+        };
+        if expected.is_some_and(|expected| expected != self.result) {
+            return Err(ChessError::ResultMismatch {
+                result: self.result,
+                outcome,
+            });
+        }
+        if let Some(termination) = self.tag("Termination") {
+            let normal = termination.eq_ignore_ascii_case("normal");
+            let unterminated = termination.eq_ignore_ascii_case("unterminated");
+            let finished = self.result != GameResult::Unknown;
+            if (outcome.is_over() && !normal) || (normal && !finished) || (unterminated && finished) {
+                return Err(ChessError::TerminationMismatch {
+                    termination: termination.to_string(),
+                    result: self.result,
+                    outcome,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Halfmove of the game the first move is, counting from 0 for white's first move
+    fn starting_ply(&self) -> usize {
+        let Some(Ok((_, fen))) = self.starting_fen().map(parser::fen::fen) else {
+            return 0;
+        };
+        let black_to_move = usize::from(fen.turn == PieceColour::Black);
+        (fen.fullmove_number.max(1) as usize - 1) * 2 + black_to_move
+    }
+
+    /// Return as string in PGN export format
+    ///
+    /// Tags of the Seven Tag Roster that are missing are written with unknown values, and the
+    /// `Result` tag defaults to the game termination marker. Whitespace inside comments is
+    /// collapsed into single spaces so that comments can be wrapped, and closing braces are removed
+    /// from them since they would end the comment early.
+    pub fn as_pgn_str(&self) -> String {
+        let mut s = String::new();
+        for name in SEVEN_TAG_ROSTER {
+            let value = self.tag(name).unwrap_or(match name {
+                "Date" => "????.??.??",
+                "Result" => self.result.as_str(),
+                _ => "?",
+            });
+            write_tag_pair(&mut s, name, value);
+        }
+        let mut other_tags: Vec<_> = self
+            .tags
+            .iter()
+            .filter(|(name, _)| !SEVEN_TAG_ROSTER.contains(&name.as_str()))
+            .collect();
+        other_tags.sort_by_key(|(name, _)| name);
+        for (name, value) in other_tags {
+            write_tag_pair(&mut s, name, value);
+        }
+        s.push('\n');
+
+        let mut tokens = vec![];
+        variation_tokens(&self.mainline, self.starting_ply(), &mut tokens);
+        tokens.push(self.result.to_string());
+        let mut line_length = 0;
+        for token in tokens {
+            if line_length > 0 {
+                if line_length + 1 + token.len() > EXPORT_LINE_LENGTH {
+                    s.push('\n');
+                    line_length = 0;
+                } else {
+                    s.push(' ');
+                    line_length += 1;
+                }
+            }
+            s.push_str(&token);
+            line_length += token.len();
+        }
+        s.push('\n');
+        s
+    }
+}
+
+impl fmt::Display for PgnGame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_pgn_str())
+    }
+}
+
+/// Write a tag pair on its own line, escaping quotes and backslashes in the value
+fn write_tag_pair(s: &mut String, name: &str, value: &str) {
+    s.push('[');
+    s.push_str(name);
+    s.push(' ');
+    write_string(s, value);
+    s.push_str("]\n");
+}
+
+/// Write `value` in quotes, escaping quotes and backslashes
+pub(crate) fn write_string(s: &mut String, value: &str) {
+    s.push('"');
+    for c in value.chars() {
+        if matches!(c, '"' | '\\') {
+            s.push('\\');
+        }
+        s.push(c);
+    }
+    s.push('"');
+}
+
+/// Split a comment into words so it can be wrapped, with the braces attached to the first and last
+///
+/// Comments can't contain a closing brace, so any in `comment` are left out.
+fn comment_tokens(comment: &str, tokens: &mut Vec<String>) {
+    let comment = comment.replace('}', "");
+    let mut words = comment.split_whitespace();
+    let Some(first) = words.next() else {
+        tokens.push("{}".to_string());
+        return;
+    };
+    tokens.push(format!("{{{first}"));
+    tokens.extend(words.map(str::to_string));
+    if let Some(last) = tokens.last_mut() {
+        last.push('}');
+    }
+}
+
+/// Add the tokens of `line` to `tokens`, its first move being the halfmove `ply` of the game
+///
+/// Black's moves are only numbered when they start a line or follow a comment or variation.
+fn variation_tokens(line: &Variation, mut ply: usize, tokens: &mut Vec<String>) {
+    for comment in &line.comments {
+        comment_tokens(comment, tokens);
+    }
+    let mut number_black_move = true;
+    for pgn_move in &line.moves {
+        match (ply.is_multiple_of(2), number_black_move) {
+            (true, _) => tokens.push(format!("{}.", ply / 2 + 1)),
+            (false, true) => tokens.push(format!("{}...", ply / 2 + 1)),
+            (false, false) => {}
+        }
+        tokens.push(pgn_move.chess_move.as_pgn_str());
+        tokens.extend(pgn_move.nags.iter().map(ToString::to_string));
+        for comment in &pgn_move.comments {
+            comment_tokens(comment, tokens);
+        }
+        for variation in &pgn_move.variations {
+            let start = tokens.len();
+            variation_tokens(variation, ply, tokens);
+            if tokens.len() == start {
+                tokens.push("()".to_string());
+            } else {
+                tokens[start].insert(0, '(');
+                if let Some(last) = tokens.last_mut() {
+                    last.push(')');
+                }
+            }
+        }
+        number_black_move = !pgn_move.comments.is_empty() || !pgn_move.variations.is_empty();
+        ply += 1;
+    }
+}
+
+/// Parsed game and its warnings, or the position and text of the first problem in it
+type ParsedGame = Result<(PgnGame, Vec<(usize, PgnWarningKind)>), (usize, String)>;
+
+/// Parse a single game, returning the position and text of the first problem if it can't be parsed
+///
+/// Warnings are only given when parsing leniently, each with its position in `input`.
+fn parse_game(input: &str, lenient: bool) -> ParsedGame {
+    let (text, mut warnings) = if lenient {
+        parser::pgn::normalize(input)
+    } else {
+        (String::new(), vec![])
+    };
+    let text = if lenient { text.as_str() } else { input };
+    let parsed = if lenient {
+        parser::pgn::lenient_game(text)
+    } else {
+        parser::pgn::game(text).map(|(rest, game)| (rest, (game, true)))
+    };
+    let rest = match parsed {
+        Ok((rest, (game, has_result))) if rest.trim().is_empty() => {
+            if !has_result {
+                warnings.push((text.trim_end().len(), PgnWarningKind::MissingResult));
+            }
+            return Ok((game, warnings));
+        }
+        Ok((rest, _)) => rest,
+        Err(nom::Err::Error(e) | nom::Err::Failure(e)) => e.input,
+        Err(nom::Err::Incomplete(_)) => text,
+    };
+    // The parser may have stopped before whitespace leading up to the problem
+    let rest = rest.trim_start();
+    let near = rest.split_whitespace().next().unwrap_or_default().to_string();
+    Err((text.len() - rest.len(), near))
+}
+
+/// Line of input with the byte offset and line number it starts at
+#[derive(Debug)]
+struct Line {
+    text: String,
+    offset: u64,
+    number: u64,
+}
+
+/// Iterator over the games in a PGN database, reading one game at a time
+///
+/// Games are split at the first tag pair after some movetext, ignoring tag pairs inside brace
+/// comments unless they are an `[Event` tag. Lines that aren't valid UTF-8 are read lossily. If a
+/// game can't be parsed an [`ChessError::InvalidPGNAt`] error is given with the position of the
+/// problem, and everything up to the next line starting with `[Event` is skipped.
+///
+/// Games are parsed strictly unless the reader is made with [`PgnReader::lenient`], in which case
+/// the warnings for the last game read are given by [`PgnReader::warnings`].
+///
+/// Reading stops after the first IO error, which is given as [`ChessError::Io`].
+#[derive(Debug)]
+pub struct PgnReader<R: BufRead> {
+    reader: R,
+    /// Byte offset of the next line to be read
+    offset: u64,
+    /// Number of lines read so far
+    lines_read: u64,
+    /// First line of the next game, read while looking for the end of the last one
+    pending: Option<Line>,
+    lenient: bool,
+    warnings: Vec<PgnWarning>,
+    skip_to_event: bool,
+    done: bool,
+}
+
+impl<R: BufRead> PgnReader<R> {
+    /// Read games from `reader`, parsing them strictly
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            offset: 0,
+            lines_read: 0,
+            pending: None,
+            lenient: false,
+            warnings: vec![],
+            skip_to_event: false,
+            done: false,
+        }
+    }
+
+    /// Read games from `reader`, parsing them leniently as [`PgnGame::from_pgn_str_lenient`] does
+    pub fn lenient(reader: R) -> Self {
+        Self {
+            lenient: true,
+            ..Self::new(reader)
+        }
+    }
+
+    /// Warnings for the last game read, with positions from the start of the input
+    pub fn warnings(&self) -> &[PgnWarning] {
+        &self.warnings
+    }
+
+    fn read_line(&mut self) -> io::Result<Option<Line>> {
+        if let Some(line) = self.pending.take() {
+            return Ok(Some(line));
+        }
+        let mut bytes = vec![];
+        let read = self.reader.read_until(b'\n', &mut bytes)?;
+        if read == 0 {
+            return Ok(None);
+        }
+        let line = Line {
+            text: String::from_utf8_lossy(&bytes).into_owned(),
+            offset: self.offset,
+            number: self.lines_read + 1,
+        };
+        self.offset += read as u64;
+        self.lines_read += 1;
+        Ok(Some(line))
+    }
+
+    /// Read the lines of the next game, which are empty at the end of the input
+    fn read_game(&mut self) -> io::Result<Vec<Line>> {
+        let mut lines = vec![];
+        let mut movetext = false;
+        let mut in_comment = false;
+        while let Some(line) = self.read_line()? {
+            let trimmed = line.text.trim_start();
+            if self.skip_to_event {
+                if !trimmed.starts_with("[Event") {
+                    continue;
+                }
+                self.skip_to_event = false;
+            }
+            if movetext && trimmed.starts_with('[') && (!in_comment || trimmed.starts_with("[Event")) {
+                self.pending = Some(line);
+                break;
+            }
+            movetext |= !trimmed.is_empty() && !trimmed.starts_with('[');
+            if movetext {
+                for c in trimmed.chars() {
+                    match (in_comment, c) {
+                        (false, '{') => in_comment = true,
+                        (false, ';') => break,
+                        (true, '}') => in_comment = false,
+                        _ => (),
+                    }
+                }
+            }
+            lines.push(line);
+        }
+        Ok(lines)
+    }
+}
+
+impl<R: BufRead> Iterator for PgnReader<R> {
+    type Item = Result<PgnGame, ChessError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let lines = match self.read_game() {
+            Ok(lines) => lines,
+            Err(e) => {
+                self.done = true;
+                return Some(Err(e.into()));
+            }
+        };
+        let text: String = lines.iter().map(|line| line.text.as_str()).collect();
+        if text.trim().is_empty() {
+            self.done = true;
+            return None;
+        }
+        self.warnings.clear();
+        match parse_game(&text, self.lenient) {
+            Ok((game, warnings)) => {
+                self.warnings = warnings
+                    .into_iter()
+                    .map(|(position, kind)| {
+                        let (offset, line) = locate(&lines, position);
+                        PgnWarning { offset, line, kind }
+                    })
+                    .collect();
+                Some(Ok(game))
+            }
+            Err((position, near)) => {
+                self.skip_to_event = true;
+                let (offset, line) = locate(&lines, position);
+                Some(Err(ChessError::InvalidPGNAt { offset, line, near }))
+            }
+        }
+    }
+}
+
+/// Byte offset and line number of `position` in the text made by joining `lines`
+fn locate(lines: &[Line], mut position: usize) -> (u64, u64) {
+    let mut line = &lines[0];
+    for next_line in &lines[1..] {
+        if position < line.text.len() {
+            break;
+        }
+        position -= line.text.len();
+        line = next_line;
+    }
+    (line.offset + position as u64, line.number)
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::{collection::vec, prelude::*};
+
+    use super::*;
+    use crate::board::piece_list::PieceListBoard;
+
+    fn comment_strategy() -> impl Strategy<Value = String> {
+        "[a-zA-Z0-9 .,!?(){}\n-]{0,30}"
+    }
+
+    fn line_strategy(depth: u32) -> BoxedStrategy<Variation> {
+        let variations = if depth == 0 {
+            Just(vec![]).boxed()
+        } else {
+            vec(line_strategy(depth - 1), 0..2).boxed()
+        };
+        let pgn_move = (
+            AmbiguousMove::strategy(),
+            vec(any::<u8>().prop_map(Nag), 0..2),
+            vec(comment_strategy(), 0..2),
+            variations,
+        )
+            .prop_map(|(chess_move, nags, comments, variations)| PgnMove {
+                chess_move,
+                nags,
+                comments,
+                variations,
+            });
+        (vec(comment_strategy(), 0..2), vec(pgn_move, 0..12))
+            .prop_map(|(comments, moves)| Variation { comments, moves })
+            .boxed()
+    }
+
+    fn game_strategy() -> impl Strategy<Value = PgnGame> {
+        use proptest::prop_oneof;
+
+        let result = prop_oneof![
+            Just(GameResult::WhiteWins),
+            Just(GameResult::BlackWins),
+            Just(GameResult::Draw),
+            Just(GameResult::Unknown)
+        ];
+        let tags = vec(("[A-Za-z][A-Za-z0-9_]{0,8}", "[ -~]{0,20}"), 0..10);
+        (tags, line_strategy(2), result).prop_map(|(tags, mainline, result)| PgnGame { tags, mainline, result })
+    }
+
+    #[test]
+    fn export_format() {
+        let mut game = PgnGame::new();
+        game.set_tag("White", "Paul \"The Pride\" Morphy");
+        game.set_tag("ECO", "C41");
+        game.set_tag("Annotator", "Anon");
+        game.result = GameResult::WhiteWins;
+        game.mainline.moves = ["e4", "e5", "Nf3", "d6"]
+            .into_iter()
+            .map(|s| PgnMove::new(AmbiguousMove::try_from(s).unwrap()))
+            .collect();
+        game.mainline.moves[1].nags.push(Nag(1));
+        game.mainline.moves[1]
+            .comments
+            .push("  Symmetrical\n reply ".to_string());
+        game.mainline.moves[3].comments.push("{nested} }".to_string());
+        game.mainline.moves[2].variations.push(Variation {
+            comments: vec![],
+            moves: vec![PgnMove::new(AmbiguousMove::try_from("f4").unwrap())],
+        });
+        assert_eq!(
+            game.as_pgn_str(),
+            concat!(
+                "[Event \"?\"]\n[Site \"?\"]\n[Date \"????.??.??\"]\n[Round \"?\"]\n",
+                "[White \"Paul \\\"The Pride\\\" Morphy\"]\n[Black \"?\"]\n[Result \"*\"]\n",
+                "[Annotator \"Anon\"]\n[ECO \"C41\"]\n\n",
+                "1. e4 e5 $1 {Symmetrical reply} 2. Nf3 (2. f4) 2... d6 {{nested} 1-0\n"
+            )
+        );
+    }
+
+    #[test]
+    fn numbered_from_fen() {
+        let pgn = "[SetUp \"1\"]\n[FEN \"r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 2 2\"]\n\n\
+            2... Nf6 3. Nxe5 *";
+        let game = PgnGame::from_pgn_str(pgn).unwrap();
+        assert!(game.as_pgn_str().ends_with("\n\n2... Nf6 3. Nxe5 *\n"));
+        let board: PieceListBoard = game.final_board().unwrap();
+        assert_eq!(
+            board.to_fen().to_string(),
+            "r1bqkb1r/pppp1ppp/2n2n2/4N3/4P3/8/PPPP1PPP/RNBQKB1R b KQkq - 0 3"
+        );
+
+        let mut game = game;
+        game.set_tag("SetUp", "0");
+        assert_eq!(game.starting_fen(), None);
+        assert!(game.as_pgn_str().ends_with("\n\n1. Nf6 Nxe5 *\n"));
+        assert!(matches!(
+            game.final_board::<PieceListBoard>(),
+            Err(ChessError::ImpossibleMove(_))
+        ));
+    }
+
+    #[test]
+    fn lines_wrapped() {
+        let mut game = PgnGame::new();
+        game.mainline.moves = ["Nf3", "Nf6", "Ng1", "Ng8"]
+            .into_iter()
+            .cycle()
+            .take(100)
+            .map(|s| PgnMove::new(AmbiguousMove::try_from(s).unwrap()))
+            .collect();
+        let pgn = game.as_pgn_str();
+        let movetext = pgn.split("\n\n").nth(1).unwrap();
+        assert!(movetext.lines().count() > 1);
+        for line in movetext.lines() {
+            assert!(line.len() <= EXPORT_LINE_LENGTH, "{line}");
+            assert!(!line.starts_with(' ') && !line.ends_with(' '));
+        }
+    }
+
+    const DATABASE: &str = "[Event \"First\"]\n[Result \"1-0\"]\n\n1. e4 {[%clk 0:01]\n[not a tag]} e5 1-0\n\n\
+        [Event \"Second\"]\n\n1. d4 Qx9 2. c4 *\n\
+        [Site \"Skipped\"]\n\n1. c4 *\n\n\
+        [Event \"Third\"]\n1. f4 {unclosed\n\
+        [Event \"Fourth\"]\n[Round \"1\"]\n1. Nf3 ; [Round \"2\"]\n1/2-1/2\n";
+
+    #[test]
+    fn read_database() {
+        let mut reader = PgnReader::new(DATABASE.as_bytes());
+        let first = reader.next().unwrap().unwrap();
+        assert_eq!(first.tag("Event"), Some("First"));
+        assert_eq!(first.mainline.moves[0].comments, ["[%clk 0:01]\n[not a tag]"]);
+
+        let Err(ChessError::InvalidPGNAt { offset, line, near }) = reader.next().unwrap() else {
+            panic!("Expected error in second game")
+        };
+        assert_eq!(&DATABASE[offset as usize..offset as usize + 3], "Qx9");
+        assert_eq!(DATABASE.lines().nth(line as usize - 1), Some("1. d4 Qx9 2. c4 *"));
+        assert_eq!(near, "Qx9");
+
+        // Game without an event skipped after the error, then the unclosed comment runs into the
+        // next event
+        let Err(ChessError::InvalidPGNAt { line, near, .. }) = reader.next().unwrap() else {
+            panic!("Expected error in third game")
+        };
+        assert_eq!((line, near.as_str()), (15, "{unclosed"));
+
+        let fourth = reader.next().unwrap().unwrap();
+        assert_eq!(fourth.tag("Round"), Some("1"));
+        assert_eq!(fourth.result, GameResult::Draw);
+        assert!(reader.next().is_none());
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn read_empty() {
+        assert!(PgnReader::new("\n\n".as_bytes()).next().is_none());
+    }
+
+    /// Kinds and lines of the warnings from parsing `pgn` leniently, checking strict parsing fails
+    fn lenient(pgn: &str) -> (PgnGame, Vec<(PgnWarningKind, u64)>) {
+        assert!(PgnGame::from_pgn_str(pgn).is_err(), "{pgn}");
+        let (game, warnings) = PgnGame::from_pgn_str_lenient(pgn).unwrap();
+        (
+            game,
+            warnings
+                .iter()
+                .map(|warning| (warning.kind.clone(), warning.line))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn lenient_castling_and_en_passant() {
+        let (game, warnings) = lenient("1. e4 e5 2. Nf3 Nf6 3. Bc4 Bc5 4. 0-0 0-0+ 5. exd6 e.p. *");
+        assert_eq!(
+            warnings,
+            [
+                (PgnWarningKind::ZeroCastling, 1),
+                (PgnWarningKind::ZeroCastling, 1),
+                (PgnWarningKind::EnPassantSuffix, 1)
+            ]
+        );
+        let expected = PgnGame::from_pgn_str("1. e4 e5 2. Nf3 Nf6 3. Bc4 Bc5 4. O-O O-O+ 5. exd6 *").unwrap();
+        assert_eq!(game, expected);
+    }
+
+    #[test]
+    fn lenient_figurines() {
+        let (game, warnings) = lenient("1. e4 e5 2. ♘f3 ♞c6 3. ♗b5 a6 4. e8=♕ *");
+        assert_eq!(warnings.len(), 4);
+        assert!(warnings.iter().all(|warning| *warning == (PgnWarningKind::Figurine, 1)));
+        let expected = PgnGame::from_pgn_str("1. e4 e5 2. Nf3 Nc6 3. Bb5 a6 4. e8=Q *").unwrap();
+        assert_eq!(game, expected);
+    }
+
+    #[test]
+    fn lenient_null_moves() {
+        let (game, warnings) = lenient("1. e4 e5 (1... -- 2. d4 (2. c4) exd4) 2. Nf3 -- 3. Bc4 1/2-1/2");
+        let null_move = |dropped: &[&str]| PgnWarningKind::NullMove {
+            dropped: dropped.iter().map(ToString::to_string).collect(),
+        };
+        assert_eq!(
+            warnings,
+            [(null_move(&["d4", "c4", "exd4"]), 1), (null_move(&["Bc4"]), 1)]
+        );
+        assert_eq!(warnings[1].0.to_string(), "null move removed along with Bc4");
+        assert_eq!(game.mainline.moves.len(), 3);
+        assert!(game.mainline.moves[1].variations[0].moves.is_empty());
+        assert_eq!(game.result, GameResult::Draw);
+    }
+
+    #[test]
+    fn lenient_escape_lines_and_missing_result() {
+        let (game, warnings) = lenient("% exported\n[Event \"Casual\"]\n\n1. e4 e5\n% 2. --\n2. Nf3");
+        assert_eq!(
+            warnings,
+            [
+                (PgnWarningKind::EscapeLine, 1),
+                (PgnWarningKind::EscapeLine, 5),
+                (PgnWarningKind::MissingResult, 6)
+            ]
+        );
+        assert_eq!(game.tag("Event"), Some("Casual"));
+        assert_eq!(game.mainline.moves.len(), 3);
+        assert_eq!(game.result, GameResult::Unknown);
+    }
+
+    #[test]
+    fn lenient_reader() {
+        let pgn = "[Event \"A\"]\n\n1. e4 e5 2. 0-0 *\n\n[Event \"B\"]\n\n1. d4 d5 2. c4 1-0\n";
+        let mut reader = PgnReader::lenient(pgn.as_bytes());
+        reader.next().unwrap().unwrap();
+        assert_eq!(
+            reader.warnings(),
+            [PgnWarning {
+                offset: 25,
+                line: 3,
+                kind: PgnWarningKind::ZeroCastling
+            }]
+        );
+        reader.next().unwrap().unwrap();
+        assert!(reader.warnings().is_empty());
+        assert!(reader.next().is_none());
+
+        let mut strict = PgnReader::new(pgn.as_bytes());
+        assert!(matches!(
+            strict.next(),
+            Some(Err(ChessError::InvalidPGNAt { line: 3, .. }))
+        ));
+    }
+
+    proptest! {
+        #[test]
+        fn round_trip(game in game_strategy()) {
+            let pgn = game.as_pgn_str();
+            let parsed = PgnGame::from_pgn_str(&pgn).unwrap();
+            prop_assert_eq!(parsed.as_pgn_str(), pgn);
+            prop_assert_eq!(parsed.mainline_moves().collect::<Vec<_>>(), game.mainline_moves().collect::<Vec<_>>());
+            prop_assert_eq!(parsed.result, game.result);
+        }
+    }
+}
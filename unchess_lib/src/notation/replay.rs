@@ -0,0 +1,219 @@
+//! Replaying PGN games to check that every move can be played
+//!
+//! Games are replayed on a [`BitBoard`] with
+//! [`crate::traits::LegalMoveGenerator::disambiguate_move`] and
+//! [`crate::traits::LegalMoveGenerator::move_piece_legal`], and many games are replayed in parallel
+//! with rayon. Games where every move is played also have their result checked against the final
+//! position with [`PgnGame::check_result`].
+
+use std::io::BufRead;
+
+use rayon::prelude::*;
+
+use crate::board::bitboard::BitBoard;
+use crate::error::ChessError;
+use crate::notation::pgn::{PgnGame, PgnReader};
+use crate::traits::{ChessBoard as _, LegalMoveGenerator as _};
+
+/// Number of games read from a database before they are replayed together
+const BATCH_SIZE: usize = 1024;
+
+/// Result of replaying the main line of a game
+#[derive(Debug)]
+pub struct GameReplay {
+    /// Number of halfmoves played before the first move that couldn't be, or all of them
+    pub plies: usize,
+    /// Why the halfmove after the last one played couldn't be played, if there was one
+    pub error: Option<ChessError>,
+    /// FEN of the position after the last move played, or `None` if the starting position isn't
+    /// valid
+    pub final_fen: Option<String>,
+    /// Why the result or `Termination` tag doesn't match the final position, only checked if every
+    /// move was played
+    pub result_error: Option<ChessError>,
+}
+
+impl GameReplay {
+    /// Whether every move of the main line was played
+    pub fn is_valid(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Replay the main line of `game` from its starting position
+///
+/// Stops at the first move that is illegal, matches no legal move, or matches more than one. If the
+/// starting position given by the `FEN` tag isn't valid no moves are played and there is no final
+/// FEN.
+pub fn replay_game(game: &PgnGame) -> GameReplay {
+    let mut board: BitBoard = match game.starting_board() {
+        Ok(board) => board,
+        Err(e) => {
+            return GameReplay {
+                plies: 0,
+                error: Some(e),
+                final_fen: None,
+                result_error: None,
+            };
+        }
+    };
+    let mut plies = 0;
+    let mut error = None;
+    for chess_move in game.mainline_moves() {
+        if let Err(e) = board
+            .disambiguate_move(chess_move)
+            .and_then(|unamb_move| board.move_piece_legal(unamb_move))
+        {
+            error = Some(e);
+            break;
+        }
+        plies += 1;
+    }
+    let result_error = if error.is_none() {
+        game.check_result(&board).err()
+    } else {
+        None
+    };
+    GameReplay {
+        plies,
+        error,
+        final_fen: Some(board.to_fen().to_string()),
+        result_error,
+    }
+}
+
+/// Replay every game in `games` in parallel, giving the results in the same order
+pub fn replay_games(games: &[PgnGame]) -> Vec<GameReplay> {
+    games.par_iter().map(replay_game).collect()
+}
+
+/// Replay every game in a PGN database in parallel, giving the results in the same order
+///
+/// Games are read in batches so the whole database is never held in memory. Games that can't be
+/// parsed give the error from [`PgnReader`] instead of a replay, and reading stops after an IO
+/// error.
+pub fn replay_database<R: BufRead>(reader: PgnReader<R>) -> Vec<Result<GameReplay, ChessError>> {
+    let mut replays = vec![];
+    let mut reader = reader.peekable();
+    while reader.peek().is_some() {
+        let batch: Vec<_> = reader.by_ref().take(BATCH_SIZE).collect();
+        replays.par_extend(batch.into_par_iter().map(|game| game.map(|game| replay_game(&game))));
+    }
+    replays
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enums::{AmbiguousMove, DrawReason, GameOutcome};
+    use crate::notation::pgn::GameResult;
+
+    fn game(pgn: &str) -> PgnGame {
+        PgnGame::from_pgn_str(pgn).unwrap()
+    }
+
+    #[test]
+    fn valid_game() {
+        let replay = replay_game(&game("1. f3 e5 2. g4 Qh4# 0-1"));
+        assert!(replay.is_valid());
+        assert_eq!(replay.plies, 4);
+        assert_eq!(
+            replay.final_fen.as_deref(),
+            Some("rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3")
+        );
+    }
+
+    #[test]
+    fn first_bad_ply() {
+        let replay = replay_game(&game("1. e4 e5 2. Ke3 Nc6 *"));
+        assert_eq!(replay.plies, 2);
+        assert!(matches!(replay.error, Some(ChessError::ImpossibleMove(_))));
+        assert_eq!(
+            replay.final_fen.as_deref(),
+            Some("rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq e6 0 2")
+        );
+    }
+
+    #[test]
+    fn ambiguous_ply() {
+        let replay = replay_game(&game("1. d4 d5 2. Nf3 Nf6 3. Nd2 *"));
+        assert_eq!(replay.plies, 4);
+        assert!(matches!(
+            replay.error,
+            Some(ChessError::AmbiguousMove(chess_move)) if chess_move == AmbiguousMove::try_from("Nd2").unwrap()
+        ));
+    }
+
+    #[test]
+    fn starts_from_fen() {
+        let puzzle = game("[SetUp \"1\"]\n[FEN \"6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 30\"]\n\n30. Ra8# 1-0");
+        let replay = replay_game(&puzzle);
+        assert!(replay.is_valid());
+        assert_eq!(replay.final_fen.as_deref(), Some("R5k1/5ppp/8/8/8/8/8/6K1 b - - 1 30"));
+
+        let bad_fen = game("[SetUp \"1\"]\n[FEN \"6k1/5ppp/8\"]\n\n30. Ra8# 1-0");
+        let replay = replay_game(&bad_fen);
+        assert!(matches!(replay.error, Some(ChessError::InvalidFEN(_))));
+        assert_eq!((replay.plies, replay.final_fen), (0, None));
+    }
+
+    #[test]
+    fn result_checked() {
+        assert!(replay_game(&game("1. f3 e5 2. g4 Qh4# 0-1")).result_error.is_none());
+        assert!(matches!(
+            replay_game(&game("1. f3 e5 2. g4 Qh4# 1/2-1/2")).result_error,
+            Some(ChessError::ResultMismatch {
+                result: GameResult::Draw,
+                ..
+            })
+        ));
+        assert!(replay_game(&game("1. f3 e5 2. g4 Qh4# *")).result_error.is_some());
+        assert!(replay_game(&game("1. e4 e5 2. e5 1-0")).result_error.is_none());
+
+        let stalemate = "1. e3 a5 2. Qh5 Ra6 3. Qxa5 h5 4. h4 Rah6 5. Qxc7 f6 6. Qxd7+ Kf7 7. Qxb7 Qd3 8. \
+                         Qxb8 Qh7 9. Qxc8 Kg6 10. Qe6";
+        assert!(
+            replay_game(&game(&format!("{stalemate} 1/2-1/2")))
+                .result_error
+                .is_none()
+        );
+        assert!(matches!(
+            replay_game(&game(&format!("{stalemate} 1-0"))).result_error,
+            Some(ChessError::ResultMismatch {
+                result: GameResult::WhiteWins,
+                outcome: GameOutcome::Draw(DrawReason::Stalemate)
+            })
+        ));
+    }
+
+    #[test]
+    fn termination_checked() {
+        let termination = |tag: &str, movetext: &str| {
+            replay_game(&game(&format!("[Termination \"{tag}\"]\n\n{movetext}"))).result_error
+        };
+        assert!(termination("normal", "1. f3 e5 2. g4 Qh4# 0-1").is_none());
+        assert!(termination("time forfeit", "1. e4 e5 0-1").is_none());
+        assert!(termination("unterminated", "1. e4 e5 *").is_none());
+        for (tag, movetext) in [
+            ("time forfeit", "1. f3 e5 2. g4 Qh4# 0-1"),
+            ("normal", "1. e4 e5 *"),
+            ("unterminated", "1. e4 e5 1-0"),
+        ] {
+            assert!(
+                matches!(termination(tag, movetext), Some(ChessError::TerminationMismatch { .. })),
+                "{tag} {movetext}"
+            );
+        }
+    }
+
+    #[test]
+    fn games_in_order() {
+        let games: Vec<_> = (0..100)
+            .map(|i| game(if i % 3 == 0 { "1. e4 e5 2. e5 *" } else { "1. d4 *" }))
+            .collect();
+        let replays = replay_games(&games);
+        for (i, replay) in replays.iter().enumerate() {
+            assert_eq!(replay.is_valid(), i % 3 != 0);
+        }
+    }
+}
@@ -0,0 +1,95 @@
+//! Parsing for EPD notation
+#![allow(clippy::type_complexity)]
+
+use nom::{
+    Err, IResult, Parser as _,
+    branch::alt,
+    bytes::complete::{take_till1, take_while},
+    character::complete::{char, multispace0, multispace1, satisfy},
+    combinator::recognize,
+    error::{Error, ErrorKind},
+    multi::many0,
+    sequence::{pair, preceded},
+};
+
+use crate::{
+    notation::fen::Fen,
+    parser::{fen::position, pgn::tag_value},
+};
+
+/// Parse an opcode, a letter followed by up to 14 letters, digits or underscores
+fn opcode(input: &str) -> IResult<&str, &str> {
+    let (rest, opcode) = recognize(pair(
+        satisfy(|c| c.is_ascii_alphabetic()),
+        take_while(|c: char| c.is_ascii_alphanumeric() || c == '_'),
+    ))
+    .parse(input)?;
+    if opcode.len() > 15 {
+        return Err(Err::Error(Error::new(input, ErrorKind::TooLarge)));
+    }
+    Ok((rest, opcode))
+}
+
+/// Parse an operand, either a quoted string or everything up to whitespace or the end of the
+/// operation
+fn operand(input: &str) -> IResult<&str, String> {
+    alt((
+        tag_value,
+        take_till1(|c: char| c.is_whitespace() || c == ';' || c == '"').map(str::to_string),
+    ))
+    .parse(input)
+}
+
+/// Parse an operation, an opcode followed by its operands and ended by `;`
+fn operation(input: &str) -> IResult<&str, (String, Vec<String>)> {
+    let (input, opcode) = opcode(input)?;
+    let (input, operands) = many0(preceded(multispace1, operand)).parse(input)?;
+    let (input, _) = multispace0(input)?;
+    let (input, _) = char(';')(input)?;
+    Ok((input, (opcode.to_string(), operands)))
+}
+
+/// Parse an EPD record, the first four fields of a FEN followed by any number of operations
+pub fn epd(input: &str) -> IResult<&str, (Fen, Vec<(String, Vec<String>)>)> {
+    let (input, fen) = position(input)?;
+    let (input, operations) = many0(preceded(multispace1, operation)).parse(input)?;
+    let (input, _) = multispace0(input)?;
+    Ok((input, (fen, operations)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn operations() {
+        assert_eq!(
+            operation("bm Nf3 e4;").unwrap(),
+            ("", ("bm".to_string(), vec!["Nf3".to_string(), "e4".to_string()]))
+        );
+        assert_eq!(
+            operation(r#"c0 "Fischer \"Bobby\"; 1972" ;"#).unwrap().1,
+            ("c0".to_string(), vec![r#"Fischer "Bobby"; 1972"#.to_string()])
+        );
+        assert_eq!(operation("noop;").unwrap().1, ("noop".to_string(), vec![]));
+        operation("bm Nf3").unwrap_err();
+        operation("3bm Nf3;").unwrap_err();
+        opcode("abcdefghijklmnop").unwrap_err();
+    }
+
+    #[test]
+    fn records() {
+        let (rest, (fen, operations)) =
+            epd("2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - bm Qg6; id \"WAC.001\";").unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(
+            fen.position_str(),
+            "2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - -"
+        );
+        assert_eq!(operations.len(), 2);
+        assert_eq!(operations[1], ("id".to_string(), vec!["WAC.001".to_string()]));
+
+        let (rest, (_, operations)) = epd("8/8/8/8/8/8/8/K6k w - -\n").unwrap();
+        assert_eq!((rest, operations.len()), ("", 0));
+    }
+}
@@ -3,22 +3,25 @@ use nom::{
     Err, IResult, Parser as _,
     branch::alt,
     bytes::complete::tag,
-    character::complete::{char, multispace0, one_of, u32, usize},
-    combinator::{map_res, opt, value},
+    character::complete::{char, multispace0, one_of, u8, u32, usize},
+    combinator::{map_res, opt, value, verify},
     error,
-    multi::{many1, separated_list1},
+    multi::{many0, many1, separated_list1},
+    sequence::{delimited, preceded},
 };
-#[cfg(test)]
-use proptest::prelude::Strategy;
-
 use std::fmt::Write as _;
 
 use crate::{
     enums::{CastlingSide, PieceColour, PieceKind},
+    notation::fen::{Fen, castling_index},
     parser::pgn::square,
     simple_types::{SimplePiece, SimpleSquare},
+    traits::ChessPiece as _,
 };
 
+/// Pieces on each square, indexed by rank then file
+type Layout = [[Option<SimplePiece>; 8]; 8];
+
 fn white_piece(input: &str) -> IResult<&str, SimplePiece> {
     let (input, piece_kind) = map_res(one_of("QKNBRP"), PieceKind::try_from).parse(input)?;
     Ok((input, SimplePiece::new(piece_kind, PieceColour::White)))
@@ -38,68 +41,140 @@ fn turn(input: &str) -> IResult<&str, PieceColour> {
     alt((value(PieceColour::White, tag("w")), value(PieceColour::Black, tag("b")))).parse(input)
 }
 
-fn rank(mut input: &str) -> IResult<&str, [Option<SimplePiece>; 8]> {
+/// Parse a rank of the layout, with the files of pieces marked as promoted by a `~` as bits
+///
+/// Fails if the pieces and empty squares don't add up to exactly eight files.
+fn rank(mut input: &str) -> IResult<&str, ([Option<SimplePiece>; 8], u8)> {
     let mut i = 0;
     let mut out = [None; 8];
+    let mut promoted = 0;
     while i < out.len() {
         input = if let Ok((input, piece)) = piece(input) {
             out[i] = Some(piece);
+            let (input, marker) = opt(char('~')).parse(input)?;
+            if marker.is_some() {
+                promoted |= 1 << i;
+            }
             i += 1;
             input
         } else {
-            let (input, empty_squares) = usize(input)?;
+            let (input, empty_squares) =
+                verify(usize, |&empty_squares| (1..=8 - i).contains(&empty_squares)).parse(input)?;
             i += empty_squares;
             input
         };
     }
-    Ok((input, out))
+    Ok((input, (out, promoted)))
 }
 
-fn board_layout(input: &str) -> IResult<&str, Box<[[Option<SimplePiece>; 8]; 8]>> {
-    let (input, pieces) = separated_list1(tag("/"), rank).parse(input)?;
-    if let Some(pieces) = pieces.into_iter().collect_array() {
-        Ok((input, Box::new(pieces)))
-    } else {
-        Err(Err::Error(error::Error {
+/// Parse the layout, with the squares of promoted pieces as bits indexed by rank * 8 + file
+fn board_layout(input: &str) -> IResult<&str, (Box<Layout>, u64)> {
+    let (input, ranks) = separated_list1(tag("/"), rank).parse(input)?;
+    let Some(ranks) = ranks.into_iter().collect_array::<8>() else {
+        return Err(Err::Error(error::Error {
             input,
             code: error::ErrorKind::TooLarge,
-        }))
-    }
+        }));
+    };
+    let promoted = ranks.iter().enumerate().fold(0, |promoted, (i, (_, files))| {
+        promoted | (u64::from(*files) << ((7 - i) * 8))
+    });
+    Ok((input, (Box::new(ranks.map(|(pieces, _)| pieces)), promoted)))
 }
 
-fn castling_rights(input: &str) -> IResult<&str, [bool; 4]> {
-    let (input, castles) = many1(alt((
-        |i| {
-            let (i, _) = char('K')(i)?;
-            Ok((i, Some((CastlingSide::KingSide, PieceColour::White))))
-        },
-        |i| {
-            let (i, _) = char('Q')(i)?;
-            Ok((i, Some((CastlingSide::QueenSide, PieceColour::White))))
-        },
-        |i| {
-            let (i, _) = char('k')(i)?;
-            Ok((i, Some((CastlingSide::KingSide, PieceColour::Black))))
-        },
-        |i| {
-            let (i, _) = char('q')(i)?;
-            Ok((i, Some((CastlingSide::QueenSide, PieceColour::Black))))
-        },
-        |i| {
-            let (i, _) = char('-')(i)?;
-            Ok((i, None))
-        },
-    )))
+/// Parse Crazyhouse pockets such as `[Qp]`, giving the number of each kind of piece each side has
+/// in hand
+///
+/// Fails if there are more than 255 of a kind of piece in a pocket.
+fn pockets(input: &str) -> IResult<&str, [[u8; 6]; 2]> {
+    let (input, pieces) = delimited(
+        char('['),
+        many0(verify(piece, |piece| piece.kind() != PieceKind::King)),
+        char(']'),
+    )
     .parse(input)?;
-    Ok((
-        input,
-        [
-            castles.contains(&Some((CastlingSide::KingSide, PieceColour::White))),
-            castles.contains(&Some((CastlingSide::QueenSide, PieceColour::White))),
-            castles.contains(&Some((CastlingSide::KingSide, PieceColour::Black))),
-            castles.contains(&Some((CastlingSide::QueenSide, PieceColour::Black))),
-        ],
-    ))
+    let mut pockets = [[0_u8; 6]; 2];
+    for piece in pieces {
+        let count = &mut pockets[piece.colour() as usize][piece.kind() as usize];
+        *count = count.checked_add(1).ok_or(Err::Error(error::Error {
+            input,
+            code: error::ErrorKind::TooLarge,
+        }))?;
+    }
+    Ok((input, pockets))
+}
+
+/// Parse the castling field as its letters, `KQkq` in X-FEN or rook files such as `HAha` in
+/// Shredder-FEN, or none for `-`
+fn castling_rights(input: &str) -> IResult<&str, Vec<char>> {
+    alt((value(vec![], char('-')), many1(one_of("KQkqABCDEFGHabcdefgh")))).parse(input)
+}
+
+/// File of the king of `colour` on its back rank, or the e-file if it isn't there
+pub fn king_file(layout: &[[Option<SimplePiece>; 8]; 8], colour: PieceColour) -> u8 {
+    let back_rank = &layout[back_rank_index(colour)];
+    let king = Some(SimplePiece::new(PieceKind::King, colour));
+    back_rank
+        .iter()
+        .position(|piece| *piece == king)
+        .map_or(4, |file| file as u8)
+}
+
+/// Index in the layout of the back rank of `colour`
+const fn back_rank_index(colour: PieceColour) -> usize {
+    match colour {
+        PieceColour::White => 7,
+        PieceColour::Black => 0,
+    }
+}
+
+/// File of the outermost rook of `colour` on `side` of its king on the back rank, the rook `K`,
+/// `Q`, `k` and `q` refer to in X-FEN, or the h or a-file if there isn't one
+pub fn outer_rook_file(layout: &[[Option<SimplePiece>; 8]; 8], colour: PieceColour, side: CastlingSide) -> u8 {
+    let back_rank = &layout[back_rank_index(colour)];
+    let rook = Some(SimplePiece::new(PieceKind::Rook, colour));
+    let king_file = king_file(layout, colour);
+    match side {
+        CastlingSide::KingSide => (king_file + 1..8)
+            .rev()
+            .find(|file| back_rank[*file as usize] == rook)
+            .unwrap_or(7),
+        CastlingSide::QueenSide => (0..king_file)
+            .find(|file| back_rank[*file as usize] == rook)
+            .unwrap_or(0),
+    }
+}
+
+/// Rook files of each castling right given by the letters of the castling field
+fn castling_rook_files(layout: &[[Option<SimplePiece>; 8]; 8], castles: &[char]) -> [Option<u8>; 4] {
+    let mut rights = [None; 4];
+    for &castle in castles {
+        let colour = if castle.is_ascii_uppercase() {
+            PieceColour::White
+        } else {
+            PieceColour::Black
+        };
+        let (side, file) = match castle.to_ascii_uppercase() {
+            'K' => (
+                CastlingSide::KingSide,
+                outer_rook_file(layout, colour, CastlingSide::KingSide),
+            ),
+            'Q' => (
+                CastlingSide::QueenSide,
+                outer_rook_file(layout, colour, CastlingSide::QueenSide),
+            ),
+            letter => {
+                let file = letter as u8 - b'A';
+                if file > king_file(layout, colour) {
+                    (CastlingSide::KingSide, file)
+                } else {
+                    (CastlingSide::QueenSide, file)
+                }
+            }
+        };
+        rights[castling_index(colour, side)] = Some(file);
+    }
+    rights
 }
 
 fn en_passant(input: &str) -> IResult<&str, Option<SimpleSquare>> {
@@ -116,122 +191,76 @@ fn en_passant(input: &str) -> IResult<&str, Option<SimpleSquare>> {
     .parse(input)
 }
 
-pub fn fen(input: &str) -> IResult<&str, Fen> {
+/// Parse the checks given by white and black in Three-check, written as `+N+M`, indexed by colour
+fn checks(input: &str) -> IResult<&str, [u8; 2]> {
+    let (input, white) = preceded(char('+'), u8).parse(input)?;
+    let (input, black) = preceded(char('+'), u8).parse(input)?;
+    let mut checks = [0; 2];
+    checks[PieceColour::White as usize] = white;
+    checks[PieceColour::Black as usize] = black;
+    Ok((input, checks))
+}
+
+/// Parse the first four fields of a FEN, as used by EPD, with the clocks set to 0 and 1
+pub fn position(input: &str) -> IResult<&str, Fen> {
     let (input, _) = multispace0(input)?;
-    let (input, layout) = board_layout(input)?;
+    let (input, (layout, promoted)) = board_layout(input)?;
+    let (input, pockets) = opt(pockets).parse(input)?;
     let (input, _) = multispace0(input)?;
     let (input, turn) = turn(input)?;
     let (input, _) = multispace0(input)?;
-    let (input, castling_rights) = castling_rights(input)?;
+    let (input, castles) = castling_rights(input)?;
     let (input, _) = multispace0(input)?;
     let (input, en_passant) = en_passant(input)?;
+    Ok((
+        input,
+        Fen {
+            castling_rights: castling_rook_files(&layout, &castles),
+            layout,
+            pockets,
+            promoted,
+            checks: None,
+            turn,
+            en_passant,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+        },
+    ))
+}
+
+pub fn fen(input: &str) -> IResult<&str, Fen> {
+    let (input, position) = position(input)?;
     let (input, _) = multispace0(input)?;
     let (input, halfmove_clock) = opt(u32).parse(input)?;
     let (input, _) = multispace0(input)?;
     let (input, fullmove_number) = opt(u32).parse(input)?;
+    let (input, _) = multispace0(input)?;
+    let (input, checks) = opt(checks).parse(input)?;
     Ok((
         input,
         Fen {
-            layout,
-            turn,
-            castling_rights,
-            en_passant,
             halfmove_clock: halfmove_clock.unwrap_or(0),
             fullmove_number: fullmove_number.unwrap_or(0),
+            checks,
+            ..position
         },
     ))
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
-pub struct Fen {
-    pub layout: Box<[[Option<SimplePiece>; 8]; 8]>,
-    pub turn: PieceColour,
-    pub castling_rights: [bool; 4],
-    pub en_passant: Option<SimpleSquare>,
-    pub halfmove_clock: u32,
-    pub fullmove_number: u32,
-}
-
-impl Fen {
-    pub fn to_str(&self) -> String {
-        const CASTLING_LETTERS: [char; 4] = ['K', 'Q', 'k', 'q'];
-        let mut s = String::new();
-        s.push_str(&layout_to_str(&self.layout));
-        s.push(' ');
-        match self.turn {
-            PieceColour::Black => s.push('b'),
-            PieceColour::White => s.push('w'),
-        }
-        s.push(' ');
-        for (i, castling_right) in self.castling_rights.iter().enumerate() {
-            if *castling_right {
-                s.push(CASTLING_LETTERS[i]);
-            }
-        }
-        if self.castling_rights.iter().all(|b| !b) {
-            s.push('-');
-        }
-        s.push(' ');
-        if let Some(square) = self.en_passant {
-            s.push_str(&square.to_string());
-        } else {
-            s.push('-');
-        }
-        s.push(' ');
-        write!(s, "{} {}", self.halfmove_clock, self.fullmove_number).unwrap();
-        s
-    }
-
-    #[cfg(test)]
-    pub fn strategy() -> impl Strategy<Value = Self> {
-        use proptest::{array::uniform4, collection::vec, option::of, prelude::any};
-
-        let layout = vec(vec(of(SimplePiece::strategy()), 8), 8);
-        let turn = PieceColour::strategy();
-        let castling_rights = uniform4(any::<bool>());
-        let en_passant = of(SimpleSquare::strategy());
-        let halfmove_clock = any::<u32>();
-        let fullmove_number = any::<u32>();
-        (
-            layout,
-            turn,
-            castling_rights,
-            en_passant,
-            halfmove_clock,
-            fullmove_number,
-        )
-            .prop_map(
-                |(layout, turn, castling_rights, en_passant, halfmove_clock, fullmove_number)| {
-                    let layout: Box<[[Option<SimplePiece>; 8]; 8]> = Box::new(
-                        layout
-                            .iter()
-                            .map(|arr| arr.iter().copied().collect_array().unwrap())
-                            .collect_array()
-                            .unwrap(),
-                    );
-                    Self {
-                        layout,
-                        turn,
-                        castling_rights,
-                        en_passant,
-                        halfmove_clock,
-                        fullmove_number,
-                    }
-                },
-            )
-    }
-}
-
-fn rank_to_str(rank: &[Option<SimplePiece>; 8]) -> String {
+/// Write a rank of the layout, marking pieces on the files set in `promoted` with a `~`
+fn rank_to_str(rank: &[Option<SimplePiece>; 8], promoted: u8) -> String {
     let mut s = String::new();
     let mut empty_squares = 0usize;
-    for piece in rank {
+    for (file, piece) in rank.iter().enumerate() {
         if let Some(p) = piece {
             if empty_squares > 0 {
                 write!(s, "{empty_squares}").unwrap();
                 empty_squares = 0;
             }
             s.push(char::from(*p));
+            if promoted & (1 << file) != 0 {
+                s.push('~');
+            }
         } else {
             empty_squares += 1;
         }
@@ -242,10 +271,11 @@ fn rank_to_str(rank: &[Option<SimplePiece>; 8]) -> String {
     s
 }
 
-fn layout_to_str(layout: &[[Option<SimplePiece>; 8]; 8]) -> String {
+/// Write the layout, marking pieces on the squares set in `promoted` with a `~`
+pub fn layout_to_str(layout: &[[Option<SimplePiece>; 8]; 8], promoted: u64) -> String {
     let mut s = String::new();
-    for rank in layout {
-        s.push_str(&rank_to_str(rank));
+    for (i, rank) in layout.iter().enumerate() {
+        s.push_str(&rank_to_str(rank, (promoted >> ((7 - i) * 8)) as u8));
         s.push('/');
     }
     s.pop();
@@ -256,12 +286,83 @@ fn layout_to_str(layout: &[[Option<SimplePiece>; 8]; 8]) -> String {
 mod tests {
     use super::*;
     use crate::simple_types::SimplePiece;
-    use crate::traits::ChessPiece as _;
     use proptest::array::uniform8;
     use proptest::collection::vec;
     use proptest::option::of;
     use proptest::proptest;
 
+    #[test]
+    fn castling_fields() {
+        let rights = |s: &str| fen(s).unwrap().1.castling_rights;
+        let chess960 = [Some(7), Some(5), Some(7), Some(5)];
+        assert_eq!(
+            rights("bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9"),
+            chess960
+        );
+        assert_eq!(
+            rights("bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w KQkq - 2 9"),
+            chess960
+        );
+        assert_eq!(
+            rights("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w HAa - 0 1"),
+            rights("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQq - 0 1")
+        );
+        assert_eq!(rights("4k3/8/8/8/8/8/8/3R1RK1 w F - 0 1"), [None, Some(5), None, None]);
+        assert_eq!(rights("4k3/8/8/8/8/8/8/3R1RK1 w Q - 0 1"), [None, Some(3), None, None]);
+        assert_eq!(rights("4k3/8/8/8/8/8/8/4K3 w - - 0 1"), [None; 4]);
+        fen("4k3/8/8/8/8/8/8/4K3 w X - 0 1").unwrap_err();
+    }
+
+    #[test]
+    fn rank_lengths() {
+        assert_eq!(rank("3p4").unwrap().0, "");
+        for invalid in ["9", "p8", "44", "0"] {
+            rank(invalid).unwrap_err();
+        }
+        for overfull in ["9/8/8/8/8/8/8/8 w - - 0 1", "8/8/8/8/8/8/8/7pp w - - 0 1"] {
+            overfull.parse::<Fen>().unwrap_err();
+        }
+    }
+
+    #[test]
+    fn nine_ranks() {
+        fen("8/8/8/8/8/8/8/8/8 w - - 0 1").unwrap_err();
+        fen("8/8/8/8/8/8/8/8/Q~7 w - - 0 1").unwrap_err();
+    }
+
+    #[test]
+    fn crazyhouse_fields() {
+        let (_, position) = fen("4k3/1Q~6/8/8/8/8/8/4K3[QNpp] w - - 0 1").unwrap();
+        assert_eq!(position.promoted, 1 << (6 * 8 + 1));
+        let mut pockets = [[0; 6]; 2];
+        pockets[PieceColour::White as usize][PieceKind::Queen as usize] = 1;
+        pockets[PieceColour::White as usize][PieceKind::Knight as usize] = 1;
+        pockets[PieceColour::Black as usize][PieceKind::Pawn as usize] = 2;
+        assert_eq!(position.pockets, Some(pockets));
+        assert_eq!(
+            fen("4k3/8/8/8/8/8/8/4K3[] w - - 0 1").unwrap().1.pockets,
+            Some([[0; 6]; 2])
+        );
+        assert_eq!(fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap().1.pockets, None);
+        fen("4k3/8/8/8/8/8/8/4K3[K] w - - 0 1").unwrap_err();
+        fen("4k3/8/8/8/8/8/8/4K3[Q w - - 0 1").unwrap_err();
+        let full = format!("4k3/8/8/8/8/8/8/4K3[{}] w - - 0 1", "P".repeat(255));
+        assert_eq!(
+            fen(&full).unwrap().1.pocket(PieceColour::White, PieceKind::Pawn),
+            Some(255)
+        );
+        fen(&format!("4k3/8/8/8/8/8/8/4K3[{}] w - - 0 1", "P".repeat(256))).unwrap_err();
+    }
+
+    #[test]
+    fn three_check_fields() {
+        let (_, position) = fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1 +1+2").unwrap();
+        assert_eq!(position.checks(PieceColour::White), Some(1));
+        assert_eq!(position.checks(PieceColour::Black), Some(2));
+        assert_eq!(fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap().1.checks, None);
+        assert_eq!(fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1 +1").unwrap().0, "+1");
+    }
+
     proptest! {
         #[test]
         fn pieces(p in SimplePiece::strategy()) {
@@ -270,7 +371,7 @@ mod tests {
 
         #[test]
         fn ranks(r in uniform8(of(SimplePiece::strategy()))) {
-            assert_eq!(rank(&rank_to_str(&r)).unwrap().1, r);
+            assert_eq!(rank(&rank_to_str(&r, 0)).unwrap().1, (r, 0));
         }
 
         #[test]
@@ -286,13 +387,18 @@ mod tests {
         fn layouts(l in vec(vec(of(SimplePiece::strategy()), 8), 8)) {
             #[rustfmt::skip]
             let layout: Box<[[Option<SimplePiece>; 8]; 8]> = Box::new(l.iter().map(|arr| arr.iter().copied().collect_array().unwrap()).collect_array().unwrap());
-            let s = layout_to_str(&layout);
-            assert_eq!(board_layout(&s).unwrap(), ("", layout));
+            let s = layout_to_str(&layout, 0);
+            assert_eq!(board_layout(&s).unwrap(), ("", (layout, 0)));
+        }
+
+        #[test]
+        fn castling(f in Fen::strategy()) {
+            assert_eq!(fen(&f.to_shredder_string()).unwrap(), ("", f));
         }
 
         #[test]
         fn fens(f in Fen::strategy()) {
-            assert_eq!(fen(&f.to_str()).unwrap(), ("", f));
+            assert_eq!(fen(&f.to_string()).unwrap(), ("", f));
         }
     }
 }
@@ -1,2 +1,3 @@
+pub mod epd;
 pub mod fen;
 pub mod pgn;
@@ -1,19 +1,24 @@
 //! Parsing for PGN notation
 #![allow(clippy::type_complexity)]
 
+use std::iter;
+
 use nom::{
     IResult, Parser as _,
     branch::alt,
-    bytes::complete::{is_not, tag, take_until},
-    character::complete::{char, digit1, multispace0, multispace1, one_of},
-    combinator::{map_res, opt, value},
-    multi::{many0, many1},
-    sequence::{delimited, pair, separated_pair},
+    bytes::complete::{tag, take_till, take_until, take_while1},
+    character::complete::{char, digit1, multispace0, multispace1, none_of, one_of},
+    combinator::{map_opt, map_res, opt, value},
+    multi::{fold_many0, many0, many1},
+    sequence::{delimited, pair, preceded},
 };
 
 use crate::{
     enums::{AmbiguousMove, CastlingSide, MoveAction, PieceKind},
-    notation::{char_to_file, char_to_rank},
+    notation::{
+        char_to_file, char_to_rank,
+        pgn::{GameResult, Nag, PgnGame, PgnMove, PgnWarningKind, Variation},
+    },
     simple_types::{SimpleMove, SimpleSquare},
 };
 
@@ -96,46 +101,85 @@ fn normal_move(input: &str) -> IResult<&str, AmbiguousMove> {
     ))
 }
 
+/// Parse a Crazyhouse drop such as `N@f3`, where pawn drops may leave out the `P`
+fn drop_move(input: &str) -> IResult<&str, AmbiguousMove> {
+    let (input, piece_kind) = opt(map_res(one_of("QNBRP"), PieceKind::try_from)).parse(input)?;
+    let (input, _) = char('@')(input)?;
+    let (input, dest) = square(input)?;
+    let (input, action) = opt(action).parse(input)?;
+    Ok((
+        input,
+        AmbiguousMove::Drop {
+            piece_kind: piece_kind.unwrap_or(PieceKind::Pawn),
+            dest,
+            action,
+        },
+    ))
+}
+
 /// Parse PGN standard chess move
 pub fn chess_move(input: &str) -> IResult<&str, AmbiguousMove> {
+    alt((drop_move, normal_move, castle)).parse(input)
+}
+
+fn castle(input: &str) -> IResult<&str, AmbiguousMove> {
+    let (input, side) = alt((
+        value(CastlingSide::QueenSide, tag("O-O-O")),
+        value(CastlingSide::KingSide, tag("O-O")),
+    ))
+    .parse(input)?;
+    let (input, action) = opt(action).parse(input)?;
+    Ok((input, AmbiguousMove::Castle { side, action }))
+}
+
+/// Parse a `{}` or `;` comment, returning the text inside it
+fn comment(input: &str) -> IResult<&str, String> {
     alt((
-        normal_move,
-        value(
-            AmbiguousMove::Castle {
-                side: CastlingSide::QueenSide,
-            },
-            tag("O-O-O"),
-        ),
-        value(
-            AmbiguousMove::Castle {
-                side: CastlingSide::KingSide,
-            },
-            tag("O-O"),
-        ),
+        delimited(char('{'), take_until("}"), char('}')),
+        preceded(char(';'), take_till(|c| c == '\n' || c == '\r')),
     ))
+    .map(String::from)
     .parse(input)
 }
 
-fn eol_comment(input: &str) -> IResult<&str, ()> {
-    value(
-        (), // Output is thrown away.
-        pair(char(';'), is_not("\n\r")),
-    )
+/// Parse a numeric annotation glyph, either as `$n` or one of the suffix annotations like `!?`
+fn nag(input: &str) -> IResult<&str, Nag> {
+    alt((
+        preceded(char('$'), map_res(digit1, str::parse)).map(Nag),
+        map_opt(
+            alt((tag("!!"), tag("??"), tag("!?"), tag("?!"), tag("!"), tag("?"))),
+            Nag::from_suffix,
+        ),
+    ))
     .parse(input)
 }
 
-fn enclosed_comment(input: &str) -> IResult<&str, ()> {
-    value(
-        (), // Output is thrown away.
-        (tag("{"), take_until("}"), tag("}")),
+/// Parse a quoted tag value, unescaping `\"` and `\\`
+pub fn tag_value(input: &str) -> IResult<&str, String> {
+    delimited(
+        char('"'),
+        fold_many0(
+            alt((none_of("\\\""), preceded(char('\\'), one_of("\\\"")))),
+            String::new,
+            |mut value, c| {
+                value.push(c);
+                value
+            },
+        ),
+        char('"'),
     )
     .parse(input)
 }
 
-fn tag_pair(input: &str) -> IResult<&str, (&str, &str)> {
-    let (input, pair) = delimited(char('['), is_not("]"), char(']')).parse(input)?;
-    let (_, (key, value)) = separated_pair(is_not(" "), multispace0, is_not("]")).parse(pair)?;
-    Ok((input, (key, value)))
+fn tag_pair(input: &str) -> IResult<&str, (String, String)> {
+    let (input, _) = char('[')(input)?;
+    let (input, _) = multispace0(input)?;
+    let (input, name) = take_while1(|c: char| c.is_ascii_alphanumeric() || c == '_')(input)?;
+    let (input, _) = multispace0(input)?;
+    let (input, value) = tag_value(input)?;
+    let (input, _) = multispace0(input)?;
+    let (input, _) = char(']')(input)?;
+    Ok((input, (name.to_string(), value)))
 }
 
 fn move_number(input: &str) -> IResult<&str, ()> {
@@ -144,26 +188,263 @@ fn move_number(input: &str) -> IResult<&str, ()> {
     Ok((input, ()))
 }
 
-fn move_without_comments(input: &str) -> IResult<&str, AmbiguousMove> {
-    let (input, _) = many0(alt((
-        enclosed_comment,
-        eol_comment,
-        |s| Ok((multispace1(s)?.0, ())),
-        move_number,
-    )))
-    .parse(input)?;
-    chess_move(input)
+/// Parse a game termination marker
+pub fn game_result(input: &str) -> IResult<&str, GameResult> {
+    map_res(
+        alt((tag("1-0"), tag("0-1"), tag("1/2-1/2"), tag("*"))),
+        GameResult::try_from,
+    )
+    .parse(input)
 }
 
-#[allow(clippy::type_complexity)]
-pub fn pgn(input: &str) -> IResult<&str, (Vec<(&str, &str)>, Vec<AmbiguousMove>)> {
-    let (input, tag_pairs) = many0(|s| {
-        let (s, _) = multispace0(s)?;
-        tag_pair(s)
-    })
-    .parse(input)?;
-    let (input, moves) = many1(move_without_comments).parse(input)?;
-    Ok((input, ((tag_pairs), moves)))
+/// Single item of movetext after whitespace and move numbers
+enum Element {
+    Move(AmbiguousMove),
+    Comment(String),
+    Nag(Nag),
+    Variation(Variation),
+}
+
+fn element(input: &str) -> IResult<&str, Element> {
+    alt((
+        chess_move.map(Element::Move),
+        comment.map(Element::Comment),
+        nag.map(Element::Nag),
+        delimited(char('('), variation, pair(multispace0, char(')'))).map(Element::Variation),
+    ))
+    .parse(input)
+}
+
+/// Parse a line of moves along with their comments, annotation glyphs and variations
+///
+/// Comments, glyphs and variations belong to the move before them, apart from comments before the
+/// first move which belong to the line itself. Stops at the first thing that isn't part of the
+/// line, such as a game termination marker or the `)` closing a variation.
+pub fn variation(mut input: &str) -> IResult<&str, Variation> {
+    let mut line = Variation::default();
+    loop {
+        let (rest, _) = many0(alt((value((), multispace1), move_number))).parse(input)?;
+        let Ok((rest, element)) = element(rest) else {
+            return Ok((input, line));
+        };
+        match (element, line.moves.last_mut()) {
+            (Element::Move(chess_move), _) => line.moves.push(PgnMove::new(chess_move)),
+            (Element::Comment(comment), Some(last)) => last.comments.push(comment),
+            (Element::Comment(comment), None) => line.comments.push(comment),
+            (Element::Nag(nag), Some(last)) => last.nags.push(nag),
+            (Element::Variation(variation), Some(last)) => last.variations.push(variation),
+            (Element::Nag(_) | Element::Variation(_), None) => return Ok((input, line)),
+        }
+        input = rest;
+    }
+}
+
+/// Parse the tag pairs and movetext of a game, leaving the game termination marker
+pub fn pgn(input: &str) -> IResult<&str, (Vec<(String, String)>, Variation)> {
+    let (input, tag_pairs) = many0(preceded(multispace0, tag_pair)).parse(input)?;
+    let (input, mainline) = variation(input)?;
+    Ok((input, (tag_pairs, mainline)))
+}
+
+/// Parse a complete game, which must end with a game termination marker
+pub fn game(input: &str) -> IResult<&str, PgnGame> {
+    let (input, (tags, mainline)) = pgn(input)?;
+    let (input, _) = multispace0(input)?;
+    let (input, result) = game_result(input)?;
+    Ok((input, PgnGame { tags, mainline, result }))
+}
+
+/// Parse a complete game, without needing a game termination marker
+///
+/// Also returns whether the game had a termination marker, the result being unknown if it didn't.
+pub fn lenient_game(input: &str) -> IResult<&str, (PgnGame, bool)> {
+    let (input, (tags, mainline)) = pgn(input)?;
+    let (input, _) = multispace0(input)?;
+    let (input, result) = opt(game_result).parse(input)?;
+    let game = PgnGame {
+        tags,
+        mainline,
+        result: result.unwrap_or_default(),
+    };
+    Ok((input, (game, result.is_some())))
+}
+
+/// Figurine piece symbols and the letters they stand for, pawns having no letter
+const FIGURINES: [(char, &str); 12] = [
+    ('♔', "K"),
+    ('♕', "Q"),
+    ('♖', "R"),
+    ('♗', "B"),
+    ('♘', "N"),
+    ('♙', ""),
+    ('♚', "K"),
+    ('♛', "Q"),
+    ('♜', "R"),
+    ('♝', "B"),
+    ('♞', "N"),
+    ('♟', ""),
+];
+
+/// Replace every character of `s` with spaces of the same length, keeping newlines
+fn blank(s: &str, out: &mut String) {
+    for c in s.chars() {
+        if c == '\n' {
+            out.push('\n');
+        } else {
+            out.extend(iter::repeat_n(' ', c.len_utf8()));
+        }
+    }
+}
+
+/// Rewrite a single movetext token in standard form, returning the warning for what was changed
+fn normalize_token(token: &str) -> (String, Option<PgnWarningKind>) {
+    let number_len = token
+        .find(|c: char| !c.is_ascii_digit())
+        .filter(|&len| len > 0 && token[len..].starts_with('.'))
+        .map_or(0, |len| {
+            len + token[len..].len() - token[len..].trim_start_matches('.').len()
+        });
+    let (number, chess_move) = token.split_at(number_len);
+    let mut normalized = number.to_string();
+    let mut warning = None;
+    if let Some(castle) = chess_move.strip_prefix("0-0-0") {
+        normalized.push_str("O-O-O");
+        normalized.push_str(castle);
+        warning = Some(PgnWarningKind::ZeroCastling);
+    } else if let Some(castle) = chess_move.strip_prefix("0-0") {
+        normalized.push_str("O-O");
+        normalized.push_str(castle);
+        warning = Some(PgnWarningKind::ZeroCastling);
+    } else if chess_move.contains("e.p.") {
+        normalized.push_str(&chess_move.replacen("e.p.", "", 1));
+        warning = Some(PgnWarningKind::EnPassantSuffix);
+    } else if chess_move.contains(|c| FIGURINES.iter().any(|&(figurine, _)| figurine == c)) {
+        for c in chess_move.chars() {
+            match FIGURINES.iter().find(|&&(figurine, _)| figurine == c) {
+                Some((_, letter)) => normalized.push_str(letter),
+                None => normalized.push(c),
+            }
+        }
+        warning = Some(PgnWarningKind::Figurine);
+    } else {
+        normalized.push_str(chess_move);
+    }
+    (normalized, warning)
+}
+
+/// Move in a movetext token as it would be rewritten, without any move number before it
+fn token_move(token: &str) -> Option<String> {
+    let (normalized, _) = normalize_token(token);
+    let token_move = normalized.trim_start_matches(|c: char| c.is_ascii_digit() || c == '.');
+    chess_move(token_move).is_ok().then(|| token_move.to_string())
+}
+
+/// Rewrite the things commonly found in real PGN files that the grammar doesn't allow
+///
+/// Castling written with zeros and figurine piece symbols are rewritten, and `e.p.` suffixes and
+/// `%` escape lines are removed. Null moves `--` can't be played, so they are removed along with
+/// the rest of the line they are in, and the moves removed are given with the warning. Returns the
+/// rewritten text along with the byte offset and kind of everything that was changed.
+///
+/// Everything is replaced with text of the same length, padded with spaces before it, so positions
+/// in the rewritten text are the same as in `input`.
+pub fn normalize(input: &str) -> (String, Vec<(usize, PgnWarningKind)>) {
+    let mut out = String::with_capacity(input.len());
+    let mut warnings = vec![];
+    let mut movetext = false;
+    let mut depth = 0;
+    // Depth of the line a null move was found in, if the rest of the line is being removed
+    let mut skip_depth = None;
+    // Position and length of the last token if it was a move number on its own
+    let mut last_number = None;
+    let mut i = 0;
+    while let Some(c) = input[i..].chars().next() {
+        let rest = &input[i..];
+        let escape = c == '%' && (i == 0 || input[..i].ends_with('\n'));
+        let len = match c {
+            '%' if escape => {
+                warnings.push((i, PgnWarningKind::EscapeLine));
+                rest.find('\n').unwrap_or(rest.len())
+            }
+            '[' if !movetext => rest.find('\n').unwrap_or(rest.len()),
+            '{' => rest.find('}').map_or(rest.len(), |end| end + 1),
+            ';' => rest.find('\n').unwrap_or(rest.len()),
+            '(' | ')' => 1,
+            _ if c.is_whitespace() => c.len_utf8(),
+            _ => rest
+                .find(|c: char| c.is_whitespace() || "{}();".contains(c))
+                .unwrap_or(rest.len()),
+        };
+        let text = &rest[..len];
+        i += len;
+        movetext |= !c.is_whitespace() && c != '[' && !escape;
+        let number = last_number.take();
+        match c {
+            '%' if escape => blank(text, &mut out),
+            '(' => {
+                depth += 1;
+                if skip_depth.is_some() {
+                    blank(text, &mut out);
+                } else {
+                    out.push('(');
+                }
+            }
+            ')' => {
+                if skip_depth == Some(depth) {
+                    skip_depth = None;
+                }
+                if skip_depth.is_some() {
+                    blank(text, &mut out);
+                } else {
+                    out.push(')');
+                }
+                depth -= usize::from(depth > 0);
+            }
+            _ if skip_depth.is_some() => {
+                if depth == 0 && game_result(text).is_ok_and(|(rest, _)| rest.is_empty()) {
+                    skip_depth = None;
+                    out.push_str(text);
+                } else {
+                    // The last null move warning is the one for the line being removed
+                    if let Some(token_move) = token_move(text)
+                        && let Some(dropped) = warnings.iter_mut().rev().find_map(|(_, kind)| match kind {
+                            PgnWarningKind::NullMove { dropped } => Some(dropped),
+                            _ => None,
+                        })
+                    {
+                        dropped.push(token_move);
+                    }
+                    blank(text, &mut out);
+                }
+            }
+            '{' | ';' | '[' => out.push_str(text),
+            _ if c.is_whitespace() => {
+                last_number = number;
+                out.push(c);
+            }
+            _ if text == "--" => {
+                warnings.push((i - len, PgnWarningKind::NullMove { dropped: vec![] }));
+                skip_depth = Some(depth);
+                if let Some((start, number_len)) = number {
+                    out.replace_range(start..start + number_len, &" ".repeat(number_len));
+                }
+                blank(text, &mut out);
+            }
+            _ if move_number(text).is_ok_and(|(rest, ())| rest.is_empty()) => {
+                last_number = Some((i - len, len));
+                out.push_str(text);
+            }
+            _ => {
+                let (normalized, warning) = normalize_token(text);
+                if let Some(warning) = warning {
+                    warnings.push((i - len, warning));
+                }
+                out.extend(iter::repeat_n(' ', len - normalized.len()));
+                out.push_str(&normalized);
+            }
+        }
+    }
+    (out, warnings)
 }
 
 #[cfg(test)]
@@ -177,6 +458,69 @@ mod tests {
     use proptest::prelude::*;
     use std::fmt::Write as _;
 
+    #[test]
+    fn tag_pair_escapes() {
+        assert_eq!(
+            tag_pair(r#"[Event "The \"Big\" Open \\ 1956"]"#).unwrap(),
+            ("", ("Event".to_string(), r#"The "Big" Open \ 1956"#.to_string()))
+        );
+        assert_eq!(tag_pair(r#"[Site ""]"#).unwrap().1, ("Site".to_string(), String::new()));
+        tag_pair(r#"[Site "unterminated]"#).unwrap_err();
+    }
+
+    #[test]
+    fn suffix_annotations() {
+        assert_eq!(nag("!?").unwrap(), ("", Nag(5)));
+        assert_eq!(nag("??").unwrap(), ("", Nag(4)));
+        assert_eq!(nag("$14 ").unwrap(), (" ", Nag(14)));
+        nag("$256").unwrap_err();
+    }
+
+    #[test]
+    fn comments_attached_to_moves() {
+        let (rest, line) = variation("{Start} 1. e4 {Best by test} e5 ; King's pawn\n2. Nf3 *").unwrap();
+        assert_eq!(rest, " *");
+        assert_eq!(line.comments, ["Start"]);
+        assert_eq!(line.moves.len(), 3);
+        assert_eq!(line.moves[0].comments, ["Best by test"]);
+        assert_eq!(line.moves[1].comments, [" King's pawn"]);
+        assert!(line.moves[2].comments.is_empty());
+    }
+
+    #[test]
+    fn nested_variations() {
+        let (rest, line) = variation("1. e4 $1 e5!? (1... c5 (1... e6) 2. Nf3) (1... c6) 2. Nf3 1-0").unwrap();
+        assert_eq!(rest, " 1-0");
+        assert_eq!(line.moves.len(), 3);
+        assert_eq!(line.moves[0].nags, [Nag(1)]);
+        assert_eq!(line.moves[1].nags, [Nag(5)]);
+        let variations = &line.moves[1].variations;
+        assert_eq!(variations.len(), 2);
+        assert_eq!(variations[0].moves.len(), 2);
+        assert_eq!(variations[0].moves[0].variations[0].moves.len(), 1);
+        assert_eq!(
+            variations[1].moves[0].chess_move,
+            AmbiguousMove::try_from("c6").unwrap()
+        );
+    }
+
+    #[test]
+    fn unclosed_variation() {
+        let (rest, line) = variation("1. e4 (1. d4 d5 2. c4").unwrap();
+        assert_eq!(rest, " (1. d4 d5 2. c4");
+        assert_eq!(line.moves.len(), 1);
+    }
+
+    #[test]
+    fn game_requires_result() {
+        let (rest, parsed) = game("[Result \"1/2-1/2\"]\n\n1. e4 e5 1/2-1/2\n").unwrap();
+        assert_eq!(rest, "\n");
+        assert_eq!(parsed.result, GameResult::Draw);
+        assert_eq!(parsed.tag("Result"), Some("1/2-1/2"));
+        game("1. e4 e5").unwrap_err();
+        assert_eq!(game("*").unwrap().1.mainline, Variation::default());
+    }
+
     proptest! {
         #[test]
         fn good_squares(file in 0..=7u8, rank in 0..=7u8) {
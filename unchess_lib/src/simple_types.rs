@@ -76,10 +76,8 @@ impl SimpleSquare {
     /// Strategy for valid squares
     #[cfg(test)]
     pub fn strategy() -> impl Strategy<Value = Self> {
-        use proptest::prelude::any;
-
-        let file = any::<u8>().prop_filter("Valid range for file", |x| (0..=7).contains(x));
-        let rank = any::<u8>().prop_filter("Valid range for rank", |x| (0..=7).contains(x));
+        let file = 0..=7u8;
+        let rank = 0..=7u8;
         (file, rank).prop_map(|(file, rank)| Self::new(file, rank))
     }
 }
@@ -152,7 +150,7 @@ impl SimpleMove {
             if src != dest {
                 Self::new(src, dest, promote_to)
             } else {
-                Self::from_pgn_str("a1h8").unwrap()
+                Self::new(SimpleSquare::new(0, 0), SimpleSquare::new(7, 7), None)
             }
         })
     }
@@ -8,11 +8,15 @@
 
 use std::fmt::Write as _;
 
-use crate::enums::{AmbiguousMove, BoardState, PieceColour, PieceKind};
+use crate::enums::{
+    AmbiguousMove, BoardState, CastlingSide, DrawReason, GameOutcome, MoveAction, PieceColour, PieceKind, WinReason,
+};
 use crate::error::ChessError;
 use crate::notation;
-use crate::parser::fen::{Fen, fen as fen_parser};
+use crate::notation::fen::Fen;
+use crate::parser::fen::fen as fen_parser;
 use crate::parser::pgn::chess_move as chess_move_parser;
+use crate::simple_types::{SimpleMove, SimpleSquare};
 
 /// Generic chess square
 ///
@@ -46,7 +50,7 @@ pub trait ChessSquare {
 ///
 /// For an ambiguous chess move datatype compatible with PGN notation, see
 /// [`crate::enums::AmbiguousMove`]
-pub trait ChessMove {
+pub trait ChessMove: Copy {
     /// The internal square representation
     type Square: ChessSquare;
 
@@ -59,8 +63,18 @@ pub trait ChessMove {
     /// Piece to promote to if pawn reaching end of board
     fn promote_to(&self) -> Option<PieceKind>;
 
-    /// Returns start and end position as string
+    /// Kind of piece put onto the board from the pocket, if the move is a Crazyhouse drop
+    ///
+    /// Drops have no source square, so [`ChessMove::src`] gives the square dropped on.
+    fn dropped(&self) -> Option<PieceKind> {
+        None
+    }
+
+    /// Returns start and end position as string, or the piece and square such as `N@f3` for drops
     fn as_str(&self) -> String {
+        if let Some(kind) = self.dropped() {
+            return format!("{}@{}", char::from(kind), self.dest().as_str());
+        }
         let mut s = format!("{}{}", self.src().as_str(), self.dest().as_str());
         if let Some(piece) = self.promote_to() {
             write!(&mut s, "={}", char::from(piece)).unwrap();
@@ -113,6 +127,8 @@ pub trait ChessBoard: From<Fen> {
     type Piece: ChessPiece;
     /// The representation for moves
     type Move: ChessMove;
+    /// Information needed to take back a move, returned by [`ChessBoard::make_move`]
+    type Undo;
 
     /// Return the default starting chess board
     ///
@@ -124,11 +140,46 @@ pub trait ChessBoard: From<Fen> {
         Self::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").unwrap()
     }
 
-    /// Generate board from FEN standard string
+    /// Starting board of Chess960 position number `id`, see [`Fen::chess960`]
+    ///
+    /// # Panics
+    /// Panics if `id` > 959
+    fn chess960_starting_board(id: u16) -> Self
+    where
+        Self: Sized,
+    {
+        Self::from(Fen::chess960(id))
+    }
+
+    /// Starting board of a Chess960 position chosen at random
+    fn random_chess960_starting_board() -> Self
+    where
+        Self: Sized,
+    {
+        Self::chess960_starting_board(rand::random_range(0..960))
+    }
+
+    /// Generate board from FEN standard string, checking the position is legal
     ///
     /// # Errors
-    /// [`crate::error::ChessError::InvalidFEN`] If FEN isn't valid syntax
+    /// - [`crate::error::ChessError::InvalidFEN`] If FEN isn't valid syntax
+    /// - [`crate::error::ChessError::InvalidPosition`] If the position breaks a rule checked by
+    ///   [`Fen::validate`]
     fn from_fen(fen: &str) -> Result<Self, ChessError>
+    where
+        Self: Sized,
+    {
+        let fen: Fen = fen.parse()?;
+        fen.validate()?;
+        Ok(Self::from(fen))
+    }
+
+    /// Generate board from FEN standard string without checking the position is legal, for setups
+    /// such as fairy chess problems that aren't reachable in a game
+    ///
+    /// # Errors
+    /// [`crate::error::ChessError::InvalidFEN`] If FEN isn't valid syntax
+    fn from_fen_unchecked(fen: &str) -> Result<Self, ChessError>
     where
         Self: Sized,
     {
@@ -139,6 +190,9 @@ pub trait ChessBoard: From<Fen> {
         }
     }
 
+    /// Position of the board as a FEN
+    fn to_fen(&self) -> Fen;
+
     /// Return piece at `square`
     ///
     /// Returns none if no piece present.
@@ -152,6 +206,39 @@ pub trait ChessBoard: From<Fen> {
     /// No guaranteed order.
     fn all_pieces(&self) -> impl IntoIterator<Item = Self::Piece>;
 
+    /// Colour of the side to move
+    fn turn(&self) -> PieceColour;
+
+    /// Number of halfmoves since the last capture or pawn move
+    fn halfmove_clock(&self) -> u32;
+
+    /// Number of times the current position has been reached in the game, including now
+    ///
+    /// Positions are the same if they have the same pieces on the same squares, side to move,
+    /// castling rights and en passant square. Positions from before the board was loaded from FEN
+    /// are not known so aren't counted.
+    fn repetition_count(&self) -> usize;
+
+    /// Whether neither side has enough material left to checkmate
+    ///
+    /// True for king against king, king and a single bishop or knight against king, and when the
+    /// only pieces other than kings are bishops all on the same colour of square.
+    fn insufficient_material(&self) -> bool;
+
+    /// Whether neither side can checkmate by any sequence of legal moves
+    ///
+    /// True whenever [`ChessBoard::insufficient_material`] is, and also when only kings and pawns
+    /// are left with every pawn blocked by another, no pawn able to take, and neither king able to
+    /// reach an undefended enemy pawn. Other dead positions may not be detected.
+    fn is_dead_position(&self) -> bool;
+
+    /// Zobrist hash of the current position
+    ///
+    /// Covers the pieces, side to move, castling rights and en passant file, but not the move
+    /// clocks or history. Equal positions have equal hashes across all board representations, see
+    /// [`crate::board::zobrist`].
+    fn hash(&self) -> u64;
+
     /// Moves a piece on the chess board
     ///
     /// Move piece without checking for any any kind of legality, but updating state (turn, en
@@ -159,7 +246,25 @@ pub trait ChessBoard: From<Fen> {
     ///
     /// # Errors
     /// - [`crate::error::ChessError::PieceNotFound`] if no piece present at `chess_move.src()`
-    fn move_piece(&mut self, chess_move: Self::Move) -> Result<(), ChessError>;
+    fn move_piece(&mut self, chess_move: Self::Move) -> Result<(), ChessError> {
+        self.make_move(chess_move)?;
+        Ok(())
+    }
+
+    /// Moves a piece on the chess board, returning what is needed to take the move back
+    ///
+    /// Behaves the same as [`ChessBoard::move_piece`], passing the returned value to
+    /// [`ChessBoard::unmake_move`] restores the board to exactly how it was before the move.
+    ///
+    /// # Errors
+    /// - [`crate::error::ChessError::PieceNotFound`] if no piece present at `chess_move.src()`
+    fn make_move(&mut self, chess_move: Self::Move) -> Result<Self::Undo, ChessError>;
+
+    /// Take back a move made with [`ChessBoard::make_move`]
+    ///
+    /// Moves must be taken back in the reverse order they were made in, passing an `undo` from any
+    /// other move leaves the board in an invalid state.
+    fn unmake_move(&mut self, undo: Self::Undo);
 }
 
 /// Pseudo-legal move generator
@@ -175,9 +280,8 @@ pub trait PLegalMoveGenerator: ChessBoard {
     /// [`LegalMoveGenerator::all_legal_moves`].
     ///
     /// # Errors
-    /// - [`crate::error::ChessError::InvalidBoard`] if the board is in an invalid state, for
-    ///   example if there are no pieces of the colour of the current turn or there is not one king
-    ///   of each colour on the board.
+    /// - [`crate::error::ChessError::InvalidPosition`] if there is not one king of each colour,
+    ///   only possible if the position wasn't validated when the board was made
     fn all_plegal_moves(&self) -> Result<impl IntoIterator<Item = Self::Move>, ChessError>;
 
     /// Return all pseudo-legal moves for the piece at `square`
@@ -186,9 +290,8 @@ pub trait PLegalMoveGenerator: ChessBoard {
     /// [`LegalMoveGenerator::piece_legal_moves`].
     ///
     /// # Errors
-    /// - [`crate::error::ChessError::InvalidBoard`] if the board is in an invalid state, for
-    ///   example if there are no pieces of the colour of the current turn or there is not one king
-    ///   of each colour on the board.
+    /// - [`crate::error::ChessError::InvalidPosition`] if there is not one king of each colour,
+    ///   only possible if the position wasn't validated when the board was made
     /// - [`crate::error::ChessError::PieceNotFound`] if no piece present at `square`
     fn piece_plegal_moves(&self, square: Self::Square) -> Result<impl IntoIterator<Item = Self::Move>, ChessError>;
 
@@ -198,9 +301,8 @@ pub trait PLegalMoveGenerator: ChessBoard {
     /// [`LegalMoveGenerator::is_move_legal`].
     ///
     /// # Errors
-    /// - [`crate::error::ChessError::InvalidBoard`] if the board is in an invalid state, for
-    ///   example if there are no pieces of the colour of the current turn or there is not one king
-    ///   of each colour on the board.
+    /// - [`crate::error::ChessError::InvalidPosition`] if there is not one king of each colour,
+    ///   only possible if the position wasn't validated when the board was made
     /// - [`crate::error::ChessError::PieceNotFound`] if no piece present at `chess_move.src()`
     fn is_move_plegal(&self, chess_move: Self::Move) -> Result<bool, ChessError>;
 
@@ -210,9 +312,8 @@ pub trait PLegalMoveGenerator: ChessBoard {
     /// [`LegalMoveGenerator::is_move_legal`].
     ///
     /// # Errors
-    /// - [`crate::error::ChessError::InvalidBoard`] if the board is in an invalid state, for
-    ///   example if there are no pieces of the colour of the current turn or there is not one king
-    ///   of each colour on the board.
+    /// - [`crate::error::ChessError::InvalidPosition`] if there is not one king of each colour,
+    ///   only possible if the position wasn't validated when the board was made
     /// - [`crate::error::ChessError::PieceNotFound`] if no piece present at `chess_move.src()`
     /// - [`crate::error::ChessError::IllegalMove`] if chess_move is illegal
     fn move_piece_plegal(&mut self, chess_move: Self::Move) -> Result<(), ChessError>;
@@ -226,53 +327,74 @@ pub trait LegalMoveGenerator: PLegalMoveGenerator {
     /// Return all legal moves from the current board state
     ///
     /// # Errors
-    /// - [`crate::error::ChessError::InvalidBoard`] if the board is in an invalid state, for
-    ///   example if there are no pieces of the colour of the current turn or there is not one king
-    ///   of each colour on the board.
+    /// - [`crate::error::ChessError::InvalidPosition`] if there is not one king of each colour,
+    ///   only possible if the position wasn't validated when the board was made
     fn all_legal_moves(&self) -> Result<impl IntoIterator<Item = Self::Move>, ChessError>;
 
     /// Return all legal moves for the piece at `square`
     ///
     /// # Errors
-    /// - [`crate::error::ChessError::InvalidBoard`] if the board is in an invalid state, for
-    ///   example if there are no pieces of the colour of the current turn or there is not one king
-    ///   of each colour on the board.
+    /// - [`crate::error::ChessError::InvalidPosition`] if there is not one king of each colour,
+    ///   only possible if the position wasn't validated when the board was made
     /// - [`crate::error::ChessError::PieceNotFound`] if no piece present at `square`
     fn piece_legal_moves(&self, square: Self::Square) -> Result<impl IntoIterator<Item = Self::Move>, ChessError>;
 
     /// Return true if move `chess_move` is legal
     ///
     /// # Errors
-    /// - [`crate::error::ChessError::InvalidBoard`] if the board is in an invalid state, for
-    ///   example if there are no pieces of the colour of the current turn or there is not one king
-    ///   of each colour on the board.
+    /// - [`crate::error::ChessError::InvalidPosition`] if there is not one king of each colour,
+    ///   only possible if the position wasn't validated when the board was made
     /// - [`crate::error::ChessError::PieceNotFound`] if no piece present at `chess_move.src()`
     fn is_move_legal(&self, chess_move: Self::Move) -> Result<bool, ChessError>;
 
     /// Move piece if move is legal, otherwise error
     ///
     /// # Errors
-    /// - [`crate::error::ChessError::InvalidBoard`] if the board is in an invalid state, for
-    ///   example if there are no pieces of the colour of the current turn or there is not one king
-    ///   of each colour on the board.
+    /// - [`crate::error::ChessError::InvalidPosition`] if there is not one king of each colour,
+    ///   only possible if the position wasn't validated when the board was made
     /// - [`crate::error::ChessError::PieceNotFound`] if no piece present at `chess_move.src()`
     /// - [`crate::error::ChessError::IllegalMove`] if chess_move is illegal
     fn move_piece_legal(&mut self, chess_move: Self::Move) -> Result<(), ChessError>;
 
     /// Get current board state
     ///
+    /// Only considers king safety, use [`LegalMoveGenerator::outcome`] to also account for the
+    /// rules for drawn games.
+    ///
     /// # Errors
-    /// - [`crate::error::ChessError::InvalidBoard`] if the board is in an invalid state, for
-    ///   example if there are no pieces of the colour of the current turn or there is not one king
-    ///   of each colour on the board.
+    /// - [`crate::error::ChessError::InvalidPosition`] if there is not one king of each colour,
+    ///   only possible if the position wasn't validated when the board was made
     fn state(&self) -> Result<BoardState, ChessError>;
 
+    /// Whether the side to move is in check, without generating its legal moves where the board
+    /// allows
+    ///
+    /// # Errors
+    /// - [`crate::error::ChessError::InvalidPosition`] if there is not one king of each colour,
+    ///   only possible if the position wasn't validated when the board was made
+    fn in_check(&self) -> Result<bool, ChessError> {
+        Ok(matches!(self.state()?, BoardState::Check | BoardState::Checkmate))
+    }
+
+    /// Get the outcome of the game from the current position
+    ///
+    /// Checkmate and stalemate take priority over all other results. The fivefold repetition and
+    /// seventy five move rules draw the game automatically, where the threefold repetition and
+    /// fifty move rules only allow a draw to be claimed. Legal moves are still generated for drawn
+    /// positions, so play can continue if the draw is not claimed.
+    ///
+    /// # Errors
+    /// - [`crate::error::ChessError::InvalidPosition`] if there is not one king of each colour,
+    ///   only possible if the position wasn't validated when the board was made
+    fn outcome(&self) -> Result<GameOutcome, ChessError> {
+        standard_outcome(self)
+    }
+
     /// Disambiguate AmbiguousMove type
     ///
     /// # Errors
-    /// - [`crate::error::ChessError::InvalidBoard`] if the board is in an invalid state, for
-    ///   example if there are no pieces of the colour of the current turn or there is not one king
-    ///   of each colour on the board.
+    /// - [`crate::error::ChessError::InvalidPosition`] if there is not one king of each colour,
+    ///   only possible if the position wasn't validated when the board was made
     /// - [`crate::error::ChessError::ImpossibleMove`] if no moves match the given move
     /// - [`crate::error::ChessError::AmbiguousMove`] if multiple moves match the given move
     fn disambiguate_move(&self, chess_move: AmbiguousMove) -> Result<Self::Move, ChessError>;
@@ -280,9 +402,8 @@ pub trait LegalMoveGenerator: PLegalMoveGenerator {
     /// Disambiguate move from pgn str format
     ///
     /// # Errors
-    /// - [`crate::error::ChessError::InvalidBoard`] if the board is in an invalid state, for
-    ///   example if there are no pieces of the colour of the current turn or there is not one king
-    ///   of each colour on the board.
+    /// - [`crate::error::ChessError::InvalidPosition`] if there is not one king of each colour,
+    ///   only possible if the position wasn't validated when the board was made
     /// - [`crate::error::ChessError::ImpossibleMove`] if no moves match the given move
     /// - [`crate::error::ChessError::AmbiguousMove`] if multiple moves match the given move
     fn disambiguate_move_pgn(&self, pgn: &str) -> Result<Self::Move, ChessError> {
@@ -292,4 +413,374 @@ pub trait LegalMoveGenerator: PLegalMoveGenerator {
             Err(ChessError::InvalidPGN(pgn.to_string()))
         }
     }
+
+    /// Disambiguate a move, requiring it to be written exactly in standard algebraic notation
+    ///
+    /// Unlike [`LegalMoveGenerator::disambiguate_move`], captures must be marked with `x` and only
+    /// captures, the source file or rank must only be given when needed, and check and checkmate
+    /// must be marked correctly, so the move must be exactly as
+    /// [`LegalMoveGenerator::ambiguous_move`] writes it.
+    ///
+    /// # Errors
+    /// - [`crate::error::ChessError::InvalidPosition`] if there is not one king of each colour,
+    ///   only possible if the position wasn't validated when the board was made
+    /// - [`crate::error::ChessError::ImpossibleMove`] if no moves match the given move
+    /// - [`crate::error::ChessError::AmbiguousMove`] if multiple moves match the given move, or a
+    ///   pawn capture doesn't give the source file
+    /// - [`crate::error::ChessError::MissingCaptureMarker`] if a capture isn't marked with `x`
+    /// - [`crate::error::ChessError::UnexpectedCaptureMarker`] if a move taking nothing is marked
+    ///   with `x`
+    /// - [`crate::error::ChessError::OverDisambiguated`] if the source file or rank is given when
+    ///   not needed, or the rank is given when the file would do
+    /// - [`crate::error::ChessError::MissingCheckSuffix`] if check or checkmate isn't marked
+    /// - [`crate::error::ChessError::WrongCheckSuffix`] if the move is marked as check or checkmate
+    ///   wrongly
+    fn disambiguate_move_strict(&self, chess_move: AmbiguousMove) -> Result<Self::Move, ChessError>
+    where
+        Self: Clone,
+        Self::Move: ChessMove<Square = Self::Square>,
+    {
+        let loose_move = match chess_move {
+            AmbiguousMove::Normal {
+                piece_kind,
+                src_file,
+                src_rank,
+                dest,
+                promote_to,
+                ..
+            } => AmbiguousMove::Normal {
+                piece_kind,
+                src_file,
+                src_rank,
+                takes: false,
+                dest,
+                promote_to,
+                action: None,
+            },
+            AmbiguousMove::Castle { side, .. } => AmbiguousMove::Castle { side, action: None },
+            AmbiguousMove::Drop { piece_kind, dest, .. } => AmbiguousMove::Drop {
+                piece_kind,
+                dest,
+                action: None,
+            },
+        };
+        let unamb_move = self.disambiguate_move(loose_move).map_err(|e| match e {
+            ChessError::ImpossibleMove(_) => ChessError::ImpossibleMove(chess_move),
+            ChessError::AmbiguousMove(_) => ChessError::AmbiguousMove(chess_move),
+            e => e,
+        })?;
+        let expected = match self.ambiguous_move(unamb_move) {
+            Err(ChessError::IllegalMove(_)) => return Err(ChessError::ImpossibleMove(chess_move)),
+            expected => expected?,
+        };
+        let given = chess_move;
+        if let (
+            AmbiguousMove::Normal {
+                src_file,
+                src_rank,
+                takes,
+                ..
+            },
+            AmbiguousMove::Normal {
+                src_file: expected_file,
+                src_rank: expected_rank,
+                takes: expected_takes,
+                ..
+            },
+        ) = (given, expected)
+        {
+            match (takes, expected_takes) {
+                (false, true) => return Err(ChessError::MissingCaptureMarker { given, expected }),
+                (true, false) => return Err(ChessError::UnexpectedCaptureMarker { given, expected }),
+                _ => (),
+            }
+            if (src_file.is_some() && expected_file.is_none()) || (src_rank.is_some() && expected_rank.is_none()) {
+                return Err(ChessError::OverDisambiguated { given, expected });
+            }
+            if (src_file, src_rank) != (expected_file, expected_rank) {
+                return Err(ChessError::AmbiguousMove(given));
+            }
+        }
+        match (given.action(), expected.action()) {
+            (None, Some(_)) => return Err(ChessError::MissingCheckSuffix { given, expected }),
+            (given_action, expected_action) if given_action != expected_action => {
+                return Err(ChessError::WrongCheckSuffix { given, expected });
+            }
+            _ => (),
+        }
+        // Castling must be written as castling rather than as a king move
+        if given == expected {
+            Ok(unamb_move)
+        } else {
+            Err(ChessError::ImpossibleMove(given))
+        }
+    }
+
+    /// Write `chess_move` as an [`AmbiguousMove`] in standard algebraic notation
+    ///
+    /// The inverse of [`LegalMoveGenerator::disambiguate_move`]. The source file or rank is only
+    /// given when another piece of the same kind could move to the same square, preferring the
+    /// file, apart from pawn captures which always give the file. Captures including en passant are
+    /// marked as taking, check or checkmate is found by making the move, and drops are written as
+    /// the piece and square such as `N@f3`.
+    ///
+    /// # Errors
+    /// - [`crate::error::ChessError::InvalidPosition`] if there is not one king of each colour,
+    ///   only possible if the position wasn't validated when the board was made
+    /// - [`crate::error::ChessError::PieceNotFound`] if no piece present at `chess_move.src()`
+    /// - [`crate::error::ChessError::IllegalMove`] if `chess_move` is illegal
+    fn ambiguous_move(&self, chess_move: Self::Move) -> Result<AmbiguousMove, ChessError>
+    where
+        Self: Clone,
+        Self::Move: ChessMove<Square = Self::Square>,
+    {
+        let (src, dest) = (chess_move.src(), chess_move.dest());
+        let dest = SimpleSquare::new(dest.file(), dest.rank());
+        let src = SimpleSquare::new(src.file(), src.rank());
+        if !self.is_move_legal(chess_move)? {
+            return Err(ChessError::IllegalMove(SimpleMove::new(
+                src,
+                dest,
+                chess_move.promote_to(),
+            )));
+        }
+        let mut board = self.clone();
+        board.make_move(chess_move)?;
+        let action = match board.state()? {
+            BoardState::Check => Some(MoveAction::Check),
+            BoardState::Checkmate => Some(MoveAction::Checkmate),
+            BoardState::Normal | BoardState::Stalemate => None,
+        };
+        if let Some(piece_kind) = chess_move.dropped() {
+            return Ok(AmbiguousMove::Drop {
+                piece_kind,
+                dest,
+                action,
+            });
+        }
+        let piece = self.get_piece(chess_move.src())?;
+        let piece_kind = piece.kind();
+        // A legal king move onto its own rook is castling in Chess960
+        let onto_own_piece = self
+            .get_piece(chess_move.dest())
+            .is_ok_and(|taken| taken.colour() == piece.colour());
+        if piece_kind == PieceKind::King && (src.file().abs_diff(dest.file()) == 2 || onto_own_piece) {
+            let side = if dest.file() > src.file() {
+                CastlingSide::KingSide
+            } else {
+                CastlingSide::QueenSide
+            };
+            return Ok(AmbiguousMove::Castle { side, action });
+        }
+
+        let takes =
+            self.get_piece(chess_move.dest()).is_ok() || (piece_kind == PieceKind::Pawn && src.file() != dest.file());
+        let mut rivals = vec![];
+        for other in self.all_legal_moves()? {
+            if other.dropped().is_some() {
+                continue;
+            }
+            let other_src = SimpleSquare::new(other.src().file(), other.src().rank());
+            let other_dest = SimpleSquare::new(other.dest().file(), other.dest().rank());
+            if other_dest == dest && other_src != src && self.get_piece(other.src())?.kind() == piece_kind {
+                rivals.push(other_src);
+            }
+        }
+        let (src_file, src_rank) = if piece_kind == PieceKind::Pawn {
+            (takes.then_some(src.file()), None)
+        } else if rivals.is_empty() {
+            (None, None)
+        } else if rivals.iter().all(|rival| rival.file() != src.file()) {
+            (Some(src.file()), None)
+        } else if rivals.iter().all(|rival| rival.rank() != src.rank()) {
+            (None, Some(src.rank()))
+        } else {
+            (Some(src.file()), Some(src.rank()))
+        };
+        Ok(AmbiguousMove::Normal {
+            piece_kind,
+            src_file,
+            src_rank,
+            takes,
+            dest,
+            promote_to: chess_move.promote_to(),
+            action,
+        })
+    }
+
+    /// Write `chess_move` as a string in standard algebraic notation
+    ///
+    /// See [`LegalMoveGenerator::ambiguous_move`].
+    ///
+    /// # Errors
+    /// - [`crate::error::ChessError::InvalidPosition`] if there is not one king of each colour,
+    ///   only possible if the position wasn't validated when the board was made
+    /// - [`crate::error::ChessError::PieceNotFound`] if no piece present at `chess_move.src()`
+    /// - [`crate::error::ChessError::IllegalMove`] if `chess_move` is illegal
+    fn ambiguous_move_pgn(&self, chess_move: Self::Move) -> Result<String, ChessError>
+    where
+        Self: Clone,
+        Self::Move: ChessMove<Square = Self::Square>,
+    {
+        Ok(self.ambiguous_move(chess_move)?.as_pgn_str())
+    }
+
+    /// Count the positions reachable in exactly `depth` legal moves
+    ///
+    /// Performance test used to check move generation against known results, see
+    /// <https://www.chessprogramming.org/Perft_Results>.
+    ///
+    /// # Errors
+    /// - [`crate::error::ChessError::InvalidPosition`] if there is not one king of each colour,
+    ///   only possible if the position wasn't validated when the board was made
+    fn perft(&self, depth: u32) -> Result<u64, ChessError>
+    where
+        Self: Clone,
+    {
+        perft_moves(&mut self.clone(), depth)
+    }
+
+    /// Count the positions reachable in exactly `depth` legal moves, split by first move
+    ///
+    /// Comparing the counts for each move against another move generator narrows down which line
+    /// a move generation bug is in.
+    ///
+    /// # Errors
+    /// - [`crate::error::ChessError::InvalidPosition`] if there is not one king of each colour,
+    ///   only possible if the position wasn't validated when the board was made
+    fn perft_divide(&self, depth: u32) -> Result<Vec<(Self::Move, u64)>, ChessError>
+    where
+        Self: Clone,
+    {
+        let mut board = self.clone();
+        let mut divided = vec![];
+        for chess_move in self.all_legal_moves()? {
+            let undo = board.make_move(chess_move)?;
+            divided.push((chess_move, perft_moves(&mut board, depth.saturating_sub(1))?));
+            board.unmake_move(undo);
+        }
+        Ok(divided)
+    }
+}
+
+/// Rules of a chess variant, played on any board by [`crate::variant::VariantBoard`]
+///
+/// Every hook defaults to the rules of standard chess, so a variant only overrides the rules it
+/// changes. A value of the variant holds any state it needs beyond the board, such as the number of
+/// checks given, and is kept alongside the board's undo to take moves back.
+pub trait Variant: Clone + Default {
+    /// Starting position of the variant
+    fn starting_fen() -> Fen {
+        Fen::chess960(518)
+    }
+
+    /// State of the variant for a position loaded from `fen`
+    fn from_fen(_fen: &Fen) -> Self {
+        Self::default()
+    }
+
+    /// FEN of the position, given `fen` written by the board, with any state of the variant that
+    /// FENs record
+    fn to_fen(&self, fen: Fen) -> Fen {
+        fen
+    }
+
+    /// Update the state of the variant after a piece of `kind` made `chess_move`, with `board`
+    /// given after the move
+    ///
+    /// # Errors
+    /// - [`crate::error::ChessError::InvalidPosition`] if there is not one king of each colour,
+    ///   only possible if the position wasn't validated when the board was made
+    fn after_move<B: LegalMoveGenerator>(
+        &mut self,
+        _board: &B,
+        _kind: PieceKind,
+        _chess_move: B::Move,
+    ) -> Result<(), ChessError> {
+        Ok(())
+    }
+
+    /// Whether `chess_move`, legal by the rules of standard chess, is also allowed in the variant
+    ///
+    /// # Errors
+    /// - [`crate::error::ChessError::InvalidPosition`] if there is not one king of each colour,
+    ///   only possible if the position wasn't validated when the board was made
+    fn allows_move<B: LegalMoveGenerator>(&self, _board: &B, _chess_move: B::Move) -> Result<bool, ChessError> {
+        Ok(true)
+    }
+
+    /// Outcome of the game if decided by a rule of the variant, checked before checkmate and the
+    /// rules for drawn games
+    ///
+    /// No moves are legal once the outcome is decided.
+    fn decided_outcome<B: LegalMoveGenerator>(&self, _board: &B) -> Option<GameOutcome> {
+        None
+    }
+
+    /// Whether the game is drawn for lack of material, see [`ChessBoard::insufficient_material`]
+    fn insufficient_material<B: ChessBoard>(&self, board: &B) -> bool {
+        board.insufficient_material()
+    }
+
+    /// Whether neither side can win by any sequence of legal moves, see
+    /// [`ChessBoard::is_dead_position`]
+    fn is_dead_position<B: ChessBoard>(&self, board: &B) -> bool {
+        board.is_dead_position()
+    }
+
+    /// Hash of the state of the variant, xored into the hash of the board so that positions only
+    /// differing in it aren't repetitions, zero if the variant has no state
+    fn hash(&self) -> u64 {
+        0
+    }
+}
+
+/// Outcome of the game by the rules of standard chess, see [`LegalMoveGenerator::outcome`]
+pub(crate) fn standard_outcome<B: LegalMoveGenerator>(board: &B) -> Result<GameOutcome, ChessError> {
+    const FIFTY_MOVE_HALFMOVES: u32 = 100;
+    const SEVENTY_FIVE_MOVE_HALFMOVES: u32 = 150;
+    match board.state()? {
+        BoardState::Checkmate => {
+            return Ok(GameOutcome::Win {
+                winner: !board.turn(),
+                reason: WinReason::Checkmate,
+            });
+        }
+        BoardState::Stalemate => return Ok(GameOutcome::Draw(DrawReason::Stalemate)),
+        BoardState::Normal | BoardState::Check => (),
+    }
+    let repetitions = board.repetition_count();
+    Ok(if board.insufficient_material() {
+        GameOutcome::Draw(DrawReason::InsufficientMaterial)
+    } else if board.is_dead_position() {
+        GameOutcome::Draw(DrawReason::DeadPosition)
+    } else if repetitions >= 5 {
+        GameOutcome::Draw(DrawReason::FivefoldRepetition)
+    } else if board.halfmove_clock() >= SEVENTY_FIVE_MOVE_HALFMOVES {
+        GameOutcome::Draw(DrawReason::SeventyFiveMoveRule)
+    } else if repetitions >= 3 {
+        GameOutcome::ClaimableDraw(DrawReason::ThreefoldRepetition)
+    } else if board.halfmove_clock() >= FIFTY_MOVE_HALFMOVES {
+        GameOutcome::ClaimableDraw(DrawReason::FiftyMoveRule)
+    } else {
+        GameOutcome::Ongoing
+    })
+}
+
+/// Recursive part of [`LegalMoveGenerator::perft`], making and unmaking moves on a single board
+fn perft_moves<B: LegalMoveGenerator>(board: &mut B, depth: u32) -> Result<u64, ChessError> {
+    match depth {
+        0 => Ok(1),
+        1 => Ok(board.all_legal_moves()?.into_iter().count() as u64),
+        _ => {
+            let moves: Vec<B::Move> = board.all_legal_moves()?.into_iter().collect();
+            let mut nodes = 0;
+            for chess_move in moves {
+                let undo = board.make_move(chess_move)?;
+                nodes += perft_moves(board, depth - 1)?;
+                board.unmake_move(undo);
+            }
+            Ok(nodes)
+        }
+    }
 }
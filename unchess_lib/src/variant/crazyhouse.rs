@@ -0,0 +1,631 @@
+//! Crazyhouse, where captured pieces join the pocket of the side that took them
+//!
+//! Instead of moving a piece, a side may drop a piece from its pocket onto any empty square, apart
+//! from pawns onto the first or last rank. Pieces promoted from pawns are tracked, and go into the
+//! pocket as pawns when taken. Moves are [`CrazyhouseMove`]s, either a move of a piece on the board
+//! or a drop, written as the piece and square such as `N@f3`.
+//!
+//! Since pieces are never lost for good, a position from before a capture or pawn move can come up
+//! again, so repetitions are searched for through the whole game.
+
+use core::fmt;
+
+use crate::board::bitboard::{BitBoard, BitMove, BitPiece, BitSquare, BitUndo};
+use crate::board::zobrist;
+use crate::enums::{AmbiguousMove, BoardState, MoveAction, PieceColour, PieceKind};
+use crate::error::ChessError;
+use crate::notation::fen::Fen;
+use crate::traits::{
+    ChessBoard, ChessMove, ChessPiece as _, ChessSquare as _, LegalMoveGenerator, PLegalMoveGenerator,
+};
+
+/// Kinds of piece that can be held in a pocket
+const POCKET_KINDS: [PieceKind; 5] = [
+    PieceKind::Queen,
+    PieceKind::Rook,
+    PieceKind::Bishop,
+    PieceKind::Knight,
+    PieceKind::Pawn,
+];
+
+/// Move on a [`CrazyhouseBoard`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CrazyhouseMove {
+    /// Move of a piece on the board
+    Board(BitMove),
+    /// Piece put onto the board from the pocket of the side to move
+    Drop {
+        /// Kind of piece dropped
+        kind: PieceKind,
+        /// Empty square dropped on
+        square: BitSquare,
+    },
+}
+
+impl ChessMove for CrazyhouseMove {
+    type Square = BitSquare;
+
+    fn src(&self) -> BitSquare {
+        match self {
+            Self::Board(chess_move) => chess_move.src(),
+            Self::Drop { square, .. } => *square,
+        }
+    }
+
+    fn dest(&self) -> BitSquare {
+        match self {
+            Self::Board(chess_move) => chess_move.dest(),
+            Self::Drop { square, .. } => *square,
+        }
+    }
+
+    fn promote_to(&self) -> Option<PieceKind> {
+        match self {
+            Self::Board(chess_move) => chess_move.promote_to(),
+            Self::Drop { .. } => None,
+        }
+    }
+
+    fn dropped(&self) -> Option<PieceKind> {
+        match self {
+            Self::Board(_) => None,
+            Self::Drop { kind, .. } => Some(*kind),
+        }
+    }
+}
+
+impl fmt::Display for CrazyhouseMove {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl From<BitMove> for CrazyhouseMove {
+    fn from(value: BitMove) -> Self {
+        Self::Board(value)
+    }
+}
+
+/// Information needed to take back a move on a [`CrazyhouseBoard`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CrazyhouseUndo {
+    undo: BitUndo,
+    pockets: [[u8; 6]; 2],
+    promoted: u64,
+}
+
+/// Crazyhouse board, a [`BitBoard`] with a pocket of pieces in hand for each side
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrazyhouseBoard {
+    board: BitBoard,
+    /// Number of each kind of piece each side has in hand, indexed by colour then kind
+    pockets: [[u8; 6]; 2],
+    /// Squares of pieces promoted from pawns
+    promoted: u64,
+    /// Hashes of every earlier position, oldest first
+    board_history: Vec<u64>,
+}
+
+impl ChessBoard for CrazyhouseBoard {
+    type Square = BitSquare;
+    type Piece = BitPiece;
+    type Move = CrazyhouseMove;
+    type Undo = CrazyhouseUndo;
+
+    fn to_fen(&self) -> Fen {
+        Fen {
+            pockets: Some(self.pockets),
+            promoted: self.promoted,
+            ..self.board.to_fen()
+        }
+    }
+
+    fn get_piece(&self, square: BitSquare) -> Result<BitPiece, ChessError> {
+        self.board.get_piece(square)
+    }
+
+    fn all_pieces(&self) -> impl IntoIterator<Item = BitPiece> {
+        self.board.all_pieces()
+    }
+
+    fn turn(&self) -> PieceColour {
+        self.board.turn()
+    }
+
+    fn halfmove_clock(&self) -> u32 {
+        self.board.halfmove_clock()
+    }
+
+    fn repetition_count(&self) -> usize {
+        // Captured pieces can be dropped back, so captures and pawn moves don't end the search
+        zobrist::repetitions(&self.board_history, self.hash(), u32::MAX) + 1
+    }
+
+    fn insufficient_material(&self) -> bool {
+        self.pockets_empty() && self.board.insufficient_material()
+    }
+
+    fn is_dead_position(&self) -> bool {
+        self.pockets_empty() && self.board.is_dead_position()
+    }
+
+    fn hash(&self) -> u64 {
+        let mut hash = self.board.hash() ^ zobrist::promoted_key(self.promoted);
+        for colour in [PieceColour::White, PieceColour::Black] {
+            for kind in POCKET_KINDS {
+                hash ^= zobrist::pocket_key(&BitPiece::new(kind, colour), self.pocket(colour, kind));
+            }
+        }
+        hash
+    }
+
+    /// Moves a piece on the chess board or drops one from the pocket, returning what is needed to
+    /// take the move back
+    ///
+    /// # Errors
+    /// - [`crate::error::ChessError::PieceNotFound`] if no piece present at `chess_move.src()` for
+    ///   a move on the board
+    /// - [`crate::error::ChessError::IllegalDrop`] if the piece dropped isn't in the pocket or the
+    ///   square dropped on isn't empty
+    /// - [`crate::error::ChessError::InvalidBoard`] if the pocket of the side moving already holds
+    ///   255 of the kind of piece taken
+    fn make_move(&mut self, chess_move: CrazyhouseMove) -> Result<CrazyhouseUndo, ChessError> {
+        let hash = self.hash();
+        let (pockets, promoted) = (self.pockets, self.promoted);
+        let undo = match chess_move {
+            CrazyhouseMove::Board(board_move) => self.make_board_move(board_move)?,
+            CrazyhouseMove::Drop { kind, square } => {
+                let turn = self.turn();
+                let pocket = &mut self.pockets[turn as usize][kind as usize];
+                if *pocket == 0 || self.board.get_piece(square).is_ok() {
+                    return Err(ChessError::IllegalDrop(kind, square.into()));
+                }
+                *pocket -= 1;
+                self.board.make_drop(kind, square)
+            }
+        };
+        self.board_history.push(hash);
+        Ok(CrazyhouseUndo {
+            undo,
+            pockets,
+            promoted,
+        })
+    }
+
+    fn unmake_move(&mut self, undo: CrazyhouseUndo) {
+        self.board.unmake_move(undo.undo);
+        self.pockets = undo.pockets;
+        self.promoted = undo.promoted;
+        self.board_history.pop();
+    }
+}
+
+impl PLegalMoveGenerator for CrazyhouseBoard {
+    fn all_plegal_moves(&self) -> Result<impl IntoIterator<Item = CrazyhouseMove>, ChessError> {
+        let mut moves: Vec<CrazyhouseMove> = self
+            .board
+            .all_plegal_moves()?
+            .into_iter()
+            .map(CrazyhouseMove::Board)
+            .collect();
+        for kind in POCKET_KINDS {
+            for square in (0..8).flat_map(|rank| (0..8).map(move |file| BitSquare::new(file, rank))) {
+                if self.is_drop_plegal(kind, square) {
+                    moves.push(CrazyhouseMove::Drop { kind, square });
+                }
+            }
+        }
+        Ok(moves)
+    }
+
+    fn piece_plegal_moves(&self, square: BitSquare) -> Result<impl IntoIterator<Item = CrazyhouseMove>, ChessError> {
+        Ok(self
+            .board
+            .piece_plegal_moves(square)?
+            .into_iter()
+            .map(CrazyhouseMove::Board))
+    }
+
+    fn is_move_plegal(&self, chess_move: CrazyhouseMove) -> Result<bool, ChessError> {
+        match chess_move {
+            CrazyhouseMove::Board(board_move) => self.board.is_move_plegal(board_move),
+            CrazyhouseMove::Drop { kind, square } => Ok(self.is_drop_plegal(kind, square)),
+        }
+    }
+
+    fn move_piece_plegal(&mut self, chess_move: CrazyhouseMove) -> Result<(), ChessError> {
+        if self.is_move_plegal(chess_move)? {
+            self.move_piece(chess_move)
+        } else {
+            Err(illegal_move(chess_move))
+        }
+    }
+}
+
+impl LegalMoveGenerator for CrazyhouseBoard {
+    fn all_legal_moves(&self) -> Result<impl IntoIterator<Item = CrazyhouseMove>, ChessError> {
+        let mut moves: Vec<CrazyhouseMove> = self
+            .board
+            .all_legal_moves()?
+            .into_iter()
+            .map(CrazyhouseMove::Board)
+            .collect();
+        moves.append(&mut self.legal_drops()?);
+        Ok(moves)
+    }
+
+    fn piece_legal_moves(&self, square: BitSquare) -> Result<impl IntoIterator<Item = CrazyhouseMove>, ChessError> {
+        Ok(self
+            .board
+            .piece_legal_moves(square)?
+            .into_iter()
+            .map(CrazyhouseMove::Board))
+    }
+
+    fn is_move_legal(&self, chess_move: CrazyhouseMove) -> Result<bool, ChessError> {
+        match chess_move {
+            CrazyhouseMove::Board(board_move) => self.board.is_move_legal(board_move),
+            CrazyhouseMove::Drop { kind, square } => Ok(self.pocket(self.turn(), kind) > 0
+                && kind != PieceKind::King
+                && self.board.drop_squares(kind)?.contains(&square)),
+        }
+    }
+
+    fn move_piece_legal(&mut self, chess_move: CrazyhouseMove) -> Result<(), ChessError> {
+        if self.is_move_legal(chess_move)? {
+            self.move_piece(chess_move)
+        } else {
+            Err(illegal_move(chess_move))
+        }
+    }
+
+    fn state(&self) -> Result<BoardState, ChessError> {
+        let no_moves = self.board.all_legal_moves()?.into_iter().next().is_none() && self.legal_drops()?.is_empty();
+        match (no_moves, self.board.king_in_check(self.turn())?) {
+            (true, true) => Ok(BoardState::Checkmate),
+            (true, false) => Ok(BoardState::Stalemate),
+            (false, true) => Ok(BoardState::Check),
+            (false, false) => Ok(BoardState::Normal),
+        }
+    }
+
+    fn in_check(&self) -> Result<bool, ChessError> {
+        self.board.in_check()
+    }
+
+    fn disambiguate_move(&self, chess_move: AmbiguousMove) -> Result<CrazyhouseMove, ChessError> {
+        // Check and checkmate are checked here, since a drop can block a check that would be
+        // checkmate in standard chess
+        let (unamb_move, action) = match chess_move {
+            AmbiguousMove::Drop {
+                piece_kind,
+                dest,
+                action,
+            } => {
+                let drop = CrazyhouseMove::Drop {
+                    kind: piece_kind,
+                    square: dest.into(),
+                };
+                if !self.is_move_legal(drop)? {
+                    return Err(ChessError::ImpossibleMove(chess_move));
+                }
+                (drop, action)
+            }
+            AmbiguousMove::Normal {
+                piece_kind,
+                src_file,
+                src_rank,
+                takes,
+                dest,
+                promote_to,
+                action,
+            } => {
+                let loose_move = AmbiguousMove::Normal {
+                    piece_kind,
+                    src_file,
+                    src_rank,
+                    takes,
+                    dest,
+                    promote_to,
+                    action: None,
+                };
+                (self.disambiguate_board_move(loose_move, chess_move)?, action)
+            }
+            AmbiguousMove::Castle { side, action } => {
+                let loose_move = AmbiguousMove::Castle { side, action: None };
+                (self.disambiguate_board_move(loose_move, chess_move)?, action)
+            }
+        };
+        if self.gives_action(unamb_move, action)? {
+            Ok(unamb_move)
+        } else {
+            Err(ChessError::ImpossibleMove(chess_move))
+        }
+    }
+}
+
+impl From<Fen> for CrazyhouseBoard {
+    fn from(value: Fen) -> Self {
+        Self {
+            pockets: value.pockets.unwrap_or_default(),
+            promoted: value.promoted,
+            board: BitBoard::from(value),
+            board_history: Vec::with_capacity(100),
+        }
+    }
+}
+
+impl fmt::Display for CrazyhouseBoard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.board)
+    }
+}
+
+impl CrazyhouseBoard {
+    /// Number of pieces of `kind` that `colour` has in hand
+    pub fn pocket(&self, colour: PieceColour, kind: PieceKind) -> u8 {
+        self.pockets[colour as usize][kind as usize]
+    }
+
+    /// Whether the piece on `square` was promoted from a pawn, so goes into the pocket as a pawn
+    /// when taken
+    pub fn is_promoted(&self, square: BitSquare) -> bool {
+        self.promoted & square.bitboard() != 0
+    }
+
+    fn pockets_empty(&self) -> bool {
+        self.pockets.iter().flatten().all(|&count| count == 0)
+    }
+
+    /// Make a move on the board, putting any piece taken into the pocket of the side moving
+    fn make_board_move(&mut self, chess_move: BitMove) -> Result<BitUndo, ChessError> {
+        let (src, dest) = (chess_move.src(), chess_move.dest());
+        let piece = self.board.get_piece(src)?;
+        let taken = match self.board.get_piece(dest) {
+            Ok(taken) if taken.colour() != piece.colour() => Some(if self.is_promoted(dest) {
+                PieceKind::Pawn
+            } else {
+                taken.kind()
+            }),
+            // Castling, written as the king taking its own rook
+            Ok(_) => None,
+            Err(_) => (piece.kind() == PieceKind::Pawn && src.file() != dest.file()).then_some(PieceKind::Pawn),
+        };
+        let pocketed = taken
+            .map(|kind| {
+                let count = self.pocket(piece.colour(), kind).checked_add(1).ok_or_else(|| {
+                    ChessError::InvalidBoard(format!("{:?} pocket can't hold another {kind:?}", piece.colour()))
+                })?;
+                Ok::<_, ChessError>((kind, count))
+            })
+            .transpose()?;
+        let undo = self.board.make_move(chess_move)?;
+        if let Some((kind, count)) = pocketed {
+            self.pockets[piece.colour() as usize][kind as usize] = count;
+        }
+        let was_promoted = self.is_promoted(src);
+        self.promoted &= !(src.bitboard() | dest.bitboard());
+        if was_promoted || chess_move.promote_to().is_some() {
+            self.promoted |= dest.bitboard();
+        }
+        Ok(undo)
+    }
+
+    /// Whether a piece of `kind` in the pocket of the side to move could be dropped on `square`,
+    /// without checking for leaving the king in check
+    fn is_drop_plegal(&self, kind: PieceKind, square: BitSquare) -> bool {
+        self.pocket(self.turn(), kind) > 0
+            && kind != PieceKind::King
+            && self.board.get_piece(square).is_err()
+            && !(kind == PieceKind::Pawn && matches!(square.rank(), 0 | 7))
+    }
+
+    /// Drops the side to move can make without leaving its king in check
+    fn legal_drops(&self) -> Result<Vec<CrazyhouseMove>, ChessError> {
+        let mut drops = vec![];
+        for kind in POCKET_KINDS {
+            if self.pocket(self.turn(), kind) > 0 {
+                let squares = self.board.drop_squares(kind)?;
+                drops.extend(squares.into_iter().map(|square| CrazyhouseMove::Drop { kind, square }));
+            }
+        }
+        Ok(drops)
+    }
+
+    /// Disambiguate `loose_move` on the board, giving errors for `chess_move`
+    fn disambiguate_board_move(
+        &self,
+        loose_move: AmbiguousMove,
+        chess_move: AmbiguousMove,
+    ) -> Result<CrazyhouseMove, ChessError> {
+        match self.board.disambiguate_move(loose_move) {
+            Ok(board_move) => Ok(CrazyhouseMove::Board(board_move)),
+            Err(ChessError::ImpossibleMove(_)) => Err(ChessError::ImpossibleMove(chess_move)),
+            Err(ChessError::AmbiguousMove(_)) => Err(ChessError::AmbiguousMove(chess_move)),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Whether making `chess_move` gives check or checkmate as `action` says, always true if
+    /// `action` is `None`
+    fn gives_action(&self, chess_move: CrazyhouseMove, action: Option<MoveAction>) -> Result<bool, ChessError> {
+        let Some(action) = action else {
+            return Ok(true);
+        };
+        let mut board = self.clone();
+        board.make_move(chess_move)?;
+        Ok(board.state()? == action.into())
+    }
+}
+
+/// Error for trying to make an illegal move or drop
+fn illegal_move(chess_move: CrazyhouseMove) -> ChessError {
+    match chess_move {
+        CrazyhouseMove::Board(board_move) => ChessError::IllegalMove(board_move.into()),
+        CrazyhouseMove::Drop { kind, square } => ChessError::IllegalDrop(kind, square.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(s: &str) -> BitSquare {
+        BitSquare::from_pgn_str(s).unwrap()
+    }
+
+    fn drop(kind: PieceKind, s: &str) -> CrazyhouseMove {
+        CrazyhouseMove::Drop {
+            kind,
+            square: square(s),
+        }
+    }
+
+    fn play(board: &mut CrazyhouseBoard, moves: &[&str]) {
+        for pgn in moves {
+            let chess_move = board.disambiguate_move_pgn(pgn).unwrap();
+            board.move_piece_legal(chess_move).unwrap();
+        }
+    }
+
+    #[test]
+    fn captures_into_pocket() {
+        let mut board = CrazyhouseBoard::starting_board();
+        play(&mut board, &["e4", "d5", "exd5", "Qxd5", "Nc3"]);
+        assert_eq!(board.pocket(PieceColour::White, PieceKind::Pawn), 1);
+        assert_eq!(board.pocket(PieceColour::Black, PieceKind::Pawn), 1);
+        assert_eq!(
+            board.to_fen().to_string(),
+            "rnb1kbnr/ppp1pppp/8/3q4/8/2N5/PPPP1PPP/R1BQKBNR[Pp] b KQkq - 1 3"
+        );
+        play(&mut board, &["Qxg2", "Bxg2", "P@e4"]);
+        assert_eq!(board.pocket(PieceColour::White, PieceKind::Queen), 1);
+        assert_eq!(board.pocket(PieceColour::Black, PieceKind::Pawn), 1);
+        assert_eq!(
+            board.get_piece(square("e4")).unwrap(),
+            BitPiece::new(PieceKind::Pawn, PieceColour::Black)
+        );
+    }
+
+    #[test]
+    fn promoted_pieces() {
+        let mut board = CrazyhouseBoard::from_fen("4k3/1P6/8/8/8/8/6r1/R3K3[] w - - 0 1").unwrap();
+        play(&mut board, &["b8=Q+", "Kd7"]);
+        assert!(board.is_promoted(square("b8")));
+        play(&mut board, &["Qb3", "Rg3"]);
+        assert!(board.is_promoted(square("b3")) && !board.is_promoted(square("b8")));
+        assert_eq!(board.to_fen().position_str(), "8/3k4/8/8/8/1Q~4r1/8/R3K3[] w - -");
+        play(&mut board, &["Ra3", "Rxb3"]);
+        assert_eq!(board.pocket(PieceColour::Black, PieceKind::Pawn), 1);
+        assert_eq!(board.pocket(PieceColour::Black, PieceKind::Queen), 0);
+        assert!(!board.is_promoted(square("b3")));
+    }
+
+    #[test]
+    fn drops() {
+        let board = CrazyhouseBoard::from_fen("4k3/8/8/8/8/8/8/4K3[Pn] w - - 0 1").unwrap();
+        assert_eq!(board.legal_drops().unwrap().len(), 48);
+        assert!(!board.is_move_legal(drop(PieceKind::Pawn, "a8")).unwrap());
+        assert!(!board.is_move_legal(drop(PieceKind::Knight, "c3")).unwrap());
+        assert!(!board.is_move_legal(drop(PieceKind::Pawn, "e1")).unwrap());
+        assert!(matches!(
+            board.clone().move_piece_legal(drop(PieceKind::Queen, "d4")),
+            Err(ChessError::IllegalDrop(PieceKind::Queen, _))
+        ));
+
+        // Only drops blocking the check are legal
+        let board = CrazyhouseBoard::from_fen("4k3/8/8/8/8/8/8/r3K3[N] w - - 0 1").unwrap();
+        assert_eq!(board.legal_drops().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn drop_blocks_checkmate() {
+        // Back rank mate in standard chess, but a piece in hand can be dropped in the way
+        let fen = "6k1/5ppp/8/8/8/8/8/R3K3[n] w - - 0 1";
+        BitBoard::from_fen(fen).unwrap().disambiguate_move_pgn("Ra8#").unwrap();
+        let mut board = CrazyhouseBoard::from_fen(fen).unwrap();
+        assert!(matches!(
+            board.disambiguate_move_pgn("Ra8#"),
+            Err(ChessError::ImpossibleMove(_))
+        ));
+        play(&mut board, &["Ra8+"]);
+        assert_eq!(board.state().unwrap(), BoardState::Check);
+        assert_eq!(board.all_legal_moves().unwrap().into_iter().count(), 5);
+        play(&mut board, &["N@f8"]);
+        assert_eq!(board.state().unwrap(), BoardState::Normal);
+    }
+
+    #[test]
+    fn notation() {
+        let board = CrazyhouseBoard::from_fen("4k3/8/8/8/8/8/8/4K3[QNp] w - - 0 1").unwrap();
+        let knight_drop = board.disambiguate_move_pgn("N@f6+").unwrap();
+        assert_eq!(knight_drop, drop(PieceKind::Knight, "f6"));
+        assert_eq!(knight_drop.as_str(), "N@f6");
+        assert_eq!(board.ambiguous_move_pgn(knight_drop).unwrap(), "N@f6+");
+        assert_eq!(board.disambiguate_move_pgn("N@f6").unwrap(), knight_drop);
+        board.disambiguate_move_pgn("N@e1").unwrap_err();
+        board.disambiguate_move_pgn("@e4").unwrap_err();
+        assert_eq!(
+            board
+                .ambiguous_move(CrazyhouseMove::Board(BitMove::from_pgn_str("e1d2").unwrap()))
+                .unwrap(),
+            AmbiguousMove::try_from("Kd2").unwrap()
+        );
+        assert!(matches!(
+            board.disambiguate_move_strict(AmbiguousMove::try_from("Q@e7").unwrap()),
+            Err(ChessError::MissingCheckSuffix { .. })
+        ));
+    }
+
+    #[test]
+    fn unmake() {
+        let mut board = CrazyhouseBoard::starting_board();
+        play(&mut board, &["e4", "d5", "exd5", "Qxd5", "Nc3", "Qa5"]);
+        let before = board.clone();
+        let mut undos = vec![];
+        for pgn in ["P@d5", "Qxa2", "Rxa2", "P@a3"] {
+            let chess_move = board.disambiguate_move_pgn(pgn).unwrap();
+            undos.push(board.make_move(chess_move).unwrap());
+        }
+        assert_eq!(board.pocket(PieceColour::White, PieceKind::Queen), 1);
+        while let Some(undo) = undos.pop() {
+            board.unmake_move(undo);
+        }
+        assert_eq!(board, before);
+    }
+
+    #[test]
+    fn repetition_through_captures() {
+        let mut board = CrazyhouseBoard::from_fen("4k3/8/8/8/8/8/8/4K3[p] w - - 0 1").unwrap();
+        let hash = board.hash();
+        assert_ne!(
+            hash,
+            CrazyhouseBoard::from_fen("4k3/8/8/8/8/8/8/4K3[] w - - 0 1")
+                .unwrap()
+                .hash()
+        );
+        play(&mut board, &["Kd1", "P@e2+", "Kxe2", "Kd8", "P@c7+", "Kxc7"]);
+        assert_eq!(board.halfmove_clock(), 0);
+        play(&mut board, &["Kf1", "Kd7", "Ke1", "Ke8"]);
+        assert_eq!(board.hash(), hash);
+        assert_eq!(board.repetition_count(), 2);
+    }
+
+    #[test]
+    fn full_pocket() {
+        let fen = format!("4k3/8/8/8/8/8/3p4/4K3[{}] w - - 0 1", "P".repeat(255));
+        let mut board = CrazyhouseBoard::from_fen(&fen).unwrap();
+        let capture = board.disambiguate_move_pgn("Kxd2").unwrap();
+        assert!(matches!(board.make_move(capture), Err(ChessError::InvalidBoard(_))));
+        assert_eq!(board.to_fen(), fen.parse().unwrap());
+    }
+
+    #[test]
+    fn promoted_hashed() {
+        let hash = |fen: &str| CrazyhouseBoard::from_fen(fen).unwrap().hash();
+        assert_ne!(
+            hash("4k3/8/8/8/8/8/8/Q3K3[] w - - 0 1"),
+            hash("4k3/8/8/8/8/8/8/Q~3K3[] w - - 0 1")
+        );
+    }
+}
@@ -0,0 +1,118 @@
+//! King of the Hill, won by the first side to bring its king to one of the four centre squares
+//!
+//! A king can only reach d4, e4, d5 or e5 by a legal move, so never onto an attacked square. As a
+//! king can always try for the centre, the game is never drawn for lack of material or as a dead
+//! position.
+
+use crate::board::bitboard::BitBoard;
+use crate::enums::{GameOutcome, PieceColour, PieceKind, WinReason};
+use crate::error::ChessError;
+use crate::notation::fen::Fen;
+use crate::simple_types::{SimplePiece, SimpleSquare};
+use crate::traits::{ChessBoard, ChessMove as _, ChessSquare, LegalMoveGenerator, Variant};
+use crate::variant::VariantBoard;
+
+/// King of the Hill played on a [`BitBoard`]
+pub type KingOfTheHillBoard = VariantBoard<BitBoard, KingOfTheHill>;
+
+/// Rules of King of the Hill, with the side whose king has reached the centre
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct KingOfTheHill {
+    winner: Option<PieceColour>,
+}
+
+/// Whether `square` is one of the four centre squares
+fn on_hill(square: &impl ChessSquare) -> bool {
+    (3..=4).contains(&square.file()) && (3..=4).contains(&square.rank())
+}
+
+impl Variant for KingOfTheHill {
+    fn from_fen(fen: &Fen) -> Self {
+        let winner = [PieceColour::White, PieceColour::Black].into_iter().find(|&colour| {
+            (0..8)
+                .flat_map(|rank| (0..8).map(move |file| SimpleSquare::new(file, rank)))
+                .any(|square| on_hill(&square) && fen.piece(square) == Some(SimplePiece::new(PieceKind::King, colour)))
+        });
+        Self { winner }
+    }
+
+    fn after_move<B: LegalMoveGenerator>(
+        &mut self,
+        board: &B,
+        kind: PieceKind,
+        chess_move: B::Move,
+    ) -> Result<(), ChessError> {
+        if kind == PieceKind::King && on_hill(&chess_move.dest()) {
+            self.winner = Some(!board.turn());
+        }
+        Ok(())
+    }
+
+    fn decided_outcome<B: LegalMoveGenerator>(&self, _: &B) -> Option<GameOutcome> {
+        self.winner.map(|winner| GameOutcome::Win {
+            winner,
+            reason: WinReason::KingOfTheHill,
+        })
+    }
+
+    fn insufficient_material<B: ChessBoard>(&self, _: &B) -> bool {
+        false
+    }
+
+    fn is_dead_position<B: ChessBoard>(&self, _: &B) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enums::DrawReason;
+
+    const KINGS: &str = "8/2k5/8/8/8/3K4/8/8 w - - 0 1";
+
+    #[test]
+    fn reaching_the_hill() {
+        let mut board = KingOfTheHillBoard::from_fen(KINGS).unwrap();
+        assert_eq!(board.outcome().unwrap(), GameOutcome::Ongoing);
+        let undo = board.make_move(board.disambiguate_move_pgn("Kd4").unwrap()).unwrap();
+        assert_eq!(
+            board.outcome().unwrap(),
+            GameOutcome::Win {
+                winner: PieceColour::White,
+                reason: WinReason::KingOfTheHill
+            }
+        );
+        assert_eq!(board.all_legal_moves().unwrap().into_iter().count(), 0);
+        board.unmake_move(undo);
+        assert_eq!(board.outcome().unwrap(), GameOutcome::Ongoing);
+
+        // Only the king counts, and kings alone aren't a draw
+        let board = KingOfTheHillBoard::from_fen("8/2k5/8/4N3/8/3K4/8/8 w - - 0 1").unwrap();
+        assert_eq!(board.outcome().unwrap(), GameOutcome::Ongoing);
+        assert_eq!(
+            BitBoard::from_fen(KINGS).unwrap().outcome().unwrap(),
+            GameOutcome::Draw(DrawReason::InsufficientMaterial)
+        );
+    }
+
+    #[test]
+    fn attacked_hill() {
+        let board = KingOfTheHillBoard::from_fen("8/8/8/2k5/8/4K3/8/8 w - - 0 1").unwrap();
+        board.disambiguate_move_pgn("Kd4").unwrap_err();
+        board.disambiguate_move_pgn("Ke4").unwrap();
+    }
+
+    #[test]
+    fn loads_winner() {
+        let board = KingOfTheHillBoard::from_fen("8/2k5/8/8/4K3/8/8/8 b - - 0 1").unwrap();
+        assert_eq!(
+            board.outcome().unwrap(),
+            GameOutcome::Win {
+                winner: PieceColour::White,
+                reason: WinReason::KingOfTheHill
+            }
+        );
+        assert_eq!(board.perft(1).unwrap(), 0);
+    }
+}
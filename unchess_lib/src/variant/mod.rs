@@ -0,0 +1,357 @@
+//! Chess variants played on the standard board representations
+//!
+//! [`VariantBoard`] wraps any board with a [`Variant`], applying the variant's rules around the
+//! board's own move generation, so a variant only needs to describe how its rules differ from
+//! standard chess. [`Standard`] changes nothing, playing the same as the board on its own.
+//!
+//! [`three_check::ThreeCheck`] and [`king_of_the_hill::KingOfTheHill`] are variants played this
+//! way. Variants that need more state than a [`Variant`] can see, such as the pockets of
+//! [`crazyhouse::CrazyhouseBoard`], are boards of their own.
+
+pub mod crazyhouse;
+pub mod king_of_the_hill;
+pub mod three_check;
+
+use crate::board::zobrist;
+use crate::enums::{AmbiguousMove, BoardState, GameOutcome, PieceColour};
+use crate::error::ChessError;
+use crate::notation::fen::Fen;
+use crate::simple_types::{SimpleMove, SimpleSquare};
+use crate::traits::{
+    ChessBoard, ChessMove, ChessPiece as _, ChessSquare as _, LegalMoveGenerator, PLegalMoveGenerator, Variant,
+    standard_outcome,
+};
+
+/// Standard chess, with every rule unchanged
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Standard;
+
+impl Variant for Standard {}
+
+/// Board playing a chess variant
+///
+/// Legal moves are those of the wrapped board that the variant allows, none once the variant
+/// decides the outcome, and moves update the state of the variant as they are made. Positions are
+/// hashed with the state of the variant, so repetitions are searched for in a history of their own,
+/// back to the last capture or pawn move.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VariantBoard<B, V> {
+    board: B,
+    variant: V,
+    /// Hashes of every earlier position, oldest first
+    board_history: Vec<u64>,
+}
+
+impl<B, V> VariantBoard<B, V> {
+    /// Board the variant is played on
+    pub fn board(&self) -> &B {
+        &self.board
+    }
+
+    /// Current state of the variant
+    pub fn variant(&self) -> &V {
+        &self.variant
+    }
+}
+
+impl<B: LegalMoveGenerator, V: Variant> VariantBoard<B, V> {
+    /// Moves out of `moves` allowed by the variant, none if the outcome is decided
+    fn allowed_moves(&self, moves: impl IntoIterator<Item = B::Move>) -> Result<Vec<B::Move>, ChessError> {
+        let mut allowed = vec![];
+        if self.variant.decided_outcome(&self.board).is_some() {
+            return Ok(allowed);
+        }
+        for chess_move in moves {
+            if self.variant.allows_move(&self.board, chess_move)? {
+                allowed.push(chess_move);
+            }
+        }
+        Ok(allowed)
+    }
+}
+
+impl<B, V> From<Fen> for VariantBoard<B, V>
+where
+    B: ChessBoard,
+    V: Variant,
+{
+    fn from(value: Fen) -> Self {
+        Self {
+            variant: V::from_fen(&value),
+            board: B::from(value),
+            board_history: vec![],
+        }
+    }
+}
+
+impl<B, V> ChessBoard for VariantBoard<B, V>
+where
+    B: LegalMoveGenerator,
+    B::Move: ChessMove<Square = B::Square>,
+    V: Variant,
+{
+    type Square = B::Square;
+    type Piece = B::Piece;
+    type Move = B::Move;
+    /// Undo of the wrapped board, and the state of the variant before the move
+    type Undo = (B::Undo, V);
+
+    fn starting_board() -> Self {
+        Self::from(V::starting_fen())
+    }
+
+    fn to_fen(&self) -> Fen {
+        self.variant.to_fen(self.board.to_fen())
+    }
+
+    fn get_piece(&self, square: B::Square) -> Result<B::Piece, ChessError> {
+        self.board.get_piece(square)
+    }
+
+    fn all_pieces(&self) -> impl IntoIterator<Item = B::Piece> {
+        self.board.all_pieces()
+    }
+
+    fn turn(&self) -> PieceColour {
+        self.board.turn()
+    }
+
+    fn halfmove_clock(&self) -> u32 {
+        self.board.halfmove_clock()
+    }
+
+    fn repetition_count(&self) -> usize {
+        zobrist::repetitions(&self.board_history, self.hash(), self.halfmove_clock()) + 1
+    }
+
+    fn insufficient_material(&self) -> bool {
+        self.variant.insufficient_material(&self.board)
+    }
+
+    fn is_dead_position(&self) -> bool {
+        self.variant.is_dead_position(&self.board)
+    }
+
+    fn hash(&self) -> u64 {
+        self.board.hash() ^ self.variant.hash()
+    }
+
+    fn make_move(&mut self, chess_move: B::Move) -> Result<Self::Undo, ChessError> {
+        let hash = self.hash();
+        let kind = self.board.get_piece(chess_move.src())?.kind();
+        let undo = self.board.make_move(chess_move)?;
+        let variant = self.variant.clone();
+        if let Err(e) = self.variant.after_move(&self.board, kind, chess_move) {
+            self.board.unmake_move(undo);
+            self.variant = variant;
+            return Err(e);
+        }
+        self.board_history.push(hash);
+        Ok((undo, variant))
+    }
+
+    fn unmake_move(&mut self, undo: Self::Undo) {
+        self.board.unmake_move(undo.0);
+        self.variant = undo.1;
+        self.board_history.pop();
+    }
+}
+
+impl<B, V> PLegalMoveGenerator for VariantBoard<B, V>
+where
+    B: LegalMoveGenerator,
+    B::Move: ChessMove<Square = B::Square>,
+    V: Variant,
+{
+    fn all_plegal_moves(&self) -> Result<impl IntoIterator<Item = B::Move>, ChessError> {
+        self.board.all_plegal_moves()
+    }
+
+    fn piece_plegal_moves(&self, square: B::Square) -> Result<impl IntoIterator<Item = B::Move>, ChessError> {
+        self.board.piece_plegal_moves(square)
+    }
+
+    fn is_move_plegal(&self, chess_move: B::Move) -> Result<bool, ChessError> {
+        self.board.is_move_plegal(chess_move)
+    }
+
+    fn move_piece_plegal(&mut self, chess_move: B::Move) -> Result<(), ChessError> {
+        if self.is_move_plegal(chess_move)? {
+            self.move_piece(chess_move)
+        } else {
+            Err(ChessError::IllegalMove(simple_move(chess_move)))
+        }
+    }
+}
+
+impl<B, V> LegalMoveGenerator for VariantBoard<B, V>
+where
+    B: LegalMoveGenerator,
+    B::Move: ChessMove<Square = B::Square>,
+    V: Variant,
+{
+    fn all_legal_moves(&self) -> Result<impl IntoIterator<Item = B::Move>, ChessError> {
+        self.allowed_moves(self.board.all_legal_moves()?)
+    }
+
+    fn piece_legal_moves(&self, square: B::Square) -> Result<impl IntoIterator<Item = B::Move>, ChessError> {
+        self.allowed_moves(self.board.piece_legal_moves(square)?)
+    }
+
+    fn is_move_legal(&self, chess_move: B::Move) -> Result<bool, ChessError> {
+        Ok(self.board.is_move_legal(chess_move)? && !self.allowed_moves([chess_move])?.is_empty())
+    }
+
+    fn move_piece_legal(&mut self, chess_move: B::Move) -> Result<(), ChessError> {
+        if self.is_move_legal(chess_move)? {
+            self.move_piece(chess_move)
+        } else {
+            Err(ChessError::IllegalMove(simple_move(chess_move)))
+        }
+    }
+
+    fn state(&self) -> Result<BoardState, ChessError> {
+        let state = self.board.state()?;
+        if self.variant.decided_outcome(&self.board).is_some()
+            || !self.allowed_moves(self.board.all_legal_moves()?)?.is_empty()
+        {
+            return Ok(state);
+        }
+        Ok(match state {
+            BoardState::Normal | BoardState::Stalemate => BoardState::Stalemate,
+            BoardState::Check | BoardState::Checkmate => BoardState::Checkmate,
+        })
+    }
+
+    fn in_check(&self) -> Result<bool, ChessError> {
+        self.board.in_check()
+    }
+
+    fn outcome(&self) -> Result<GameOutcome, ChessError> {
+        match self.variant.decided_outcome(&self.board) {
+            Some(outcome) => Ok(outcome),
+            None => standard_outcome(self),
+        }
+    }
+
+    /// Disambiguate AmbiguousMove type
+    ///
+    /// Moves are matched against the legal moves of the wrapped board, so a move is reported as
+    /// ambiguous if it matches several moves there even when the variant only allows one.
+    fn disambiguate_move(&self, chess_move: AmbiguousMove) -> Result<B::Move, ChessError> {
+        let unamb_move = self.board.disambiguate_move(chess_move)?;
+        if self.is_move_legal(unamb_move)? {
+            Ok(unamb_move)
+        } else {
+            Err(ChessError::ImpossibleMove(chess_move))
+        }
+    }
+}
+
+/// Copy of any move as a [`SimpleMove`]
+fn simple_move(chess_move: impl ChessMove) -> SimpleMove {
+    let (src, dest) = (chess_move.src(), chess_move.dest());
+    SimpleMove::new(
+        SimpleSquare::new(src.file(), src.rank()),
+        SimpleSquare::new(dest.file(), dest.rank()),
+        chess_move.promote_to(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::bitboard::BitBoard;
+    use crate::board::piece_list::PieceListBoard;
+    use crate::enums::{CastlingSide, DrawReason, PieceKind};
+
+    /// Standard chess without castling, drawn automatically after `LIMIT` halfmoves
+    #[derive(Debug, Clone, Default)]
+    struct ShortNoCastling {
+        halfmoves: u32,
+    }
+
+    impl ShortNoCastling {
+        const LIMIT: u32 = 4;
+    }
+
+    impl Variant for ShortNoCastling {
+        fn after_move<B: LegalMoveGenerator>(&mut self, _: &B, _: PieceKind, _: B::Move) -> Result<(), ChessError> {
+            self.halfmoves += 1;
+            Ok(())
+        }
+
+        fn allows_move<B: LegalMoveGenerator>(&self, board: &B, chess_move: B::Move) -> Result<bool, ChessError> {
+            let (src, dest) = (chess_move.src(), chess_move.dest());
+            Ok(![CastlingSide::KingSide, CastlingSide::QueenSide]
+                .into_iter()
+                .any(|side| {
+                    board
+                        .disambiguate_move(AmbiguousMove::Castle { side, action: None })
+                        .is_ok_and(|castle| {
+                            (castle.src().file(), castle.src().rank(), castle.dest().file())
+                                == (src.file(), src.rank(), dest.file())
+                        })
+                }))
+        }
+
+        fn decided_outcome<B: LegalMoveGenerator>(&self, _: &B) -> Option<GameOutcome> {
+            (self.halfmoves >= Self::LIMIT).then_some(GameOutcome::Draw(DrawReason::SeventyFiveMoveRule))
+        }
+    }
+
+    const CASTLE: &str = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1";
+
+    #[test]
+    fn standard() {
+        let board: VariantBoard<BitBoard, Standard> = VariantBoard::starting_board();
+        assert_eq!(board.perft(3).unwrap(), 8902);
+        assert_eq!(board.to_fen(), BitBoard::starting_board().to_fen());
+        let board: VariantBoard<PieceListBoard, Standard> = VariantBoard::from_fen(CASTLE).unwrap();
+        assert_eq!(
+            board.perft(2).unwrap(),
+            PieceListBoard::from_fen(CASTLE).unwrap().perft(2).unwrap()
+        );
+        assert_eq!(board.outcome().unwrap(), GameOutcome::Ongoing);
+    }
+
+    #[test]
+    fn hooks() {
+        let mut board: VariantBoard<BitBoard, ShortNoCastling> = VariantBoard::from_fen(CASTLE).unwrap();
+        let castle = board.board().disambiguate_move_pgn("O-O-O").unwrap();
+        assert!(board.board().is_move_legal(castle).unwrap());
+        assert!(!board.is_move_legal(castle).unwrap());
+        assert_eq!(
+            board.all_legal_moves().unwrap().into_iter().count() + 2,
+            board.board().all_legal_moves().unwrap().into_iter().count()
+        );
+        assert!(matches!(
+            board.disambiguate_move_pgn("O-O-O"),
+            Err(ChessError::ImpossibleMove(_))
+        ));
+        board.move_piece_legal(castle).unwrap_err();
+
+        let mut undos = vec![];
+        for pgn in ["Ra2", "Ra7", "Ra1"] {
+            let chess_move = board.disambiguate_move_pgn(pgn).unwrap();
+            undos.push(board.make_move(chess_move).unwrap());
+        }
+        assert_eq!(board.outcome().unwrap(), GameOutcome::Ongoing);
+        let undo = board.make_move(board.disambiguate_move_pgn("Ra8").unwrap()).unwrap();
+        assert_eq!(board.variant().halfmoves, 4);
+        assert_eq!(
+            board.outcome().unwrap(),
+            GameOutcome::Draw(DrawReason::SeventyFiveMoveRule)
+        );
+        assert_eq!(board.all_legal_moves().unwrap().into_iter().count(), 0);
+        assert_eq!(board.state().unwrap(), BoardState::Normal);
+
+        board.unmake_move(undo);
+        assert_eq!(board.variant().halfmoves, 3);
+        assert_eq!(board.perft(1).unwrap(), 28);
+        while let Some(undo) = undos.pop() {
+            board.unmake_move(undo);
+        }
+        assert_eq!(board.to_fen(), CASTLE.parse().unwrap());
+    }
+}
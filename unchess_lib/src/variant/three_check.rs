@@ -0,0 +1,172 @@
+//! Three-check, won by the first side to give check three times
+//!
+//! Checks given are counted by [`ThreeCheck`] and written in FENs after the move counters as
+//! `+N+M`, the checks given by white then black. Checkmate still wins before a third check, and as
+//! any piece but a king can give check, the game is only drawn for lack of material once nothing
+//! but the kings is left. Positions are only repeated if the same checks have been given.
+
+use crate::board::bitboard::BitBoard;
+use crate::board::zobrist;
+use crate::enums::{GameOutcome, PieceColour, PieceKind, WinReason};
+use crate::error::ChessError;
+use crate::notation::fen::Fen;
+use crate::traits::{ChessBoard, ChessPiece as _, LegalMoveGenerator, Variant};
+use crate::variant::VariantBoard;
+
+/// Three-check played on a [`BitBoard`]
+pub type ThreeCheckBoard = VariantBoard<BitBoard, ThreeCheck>;
+
+/// Rules of Three-check, with the number of checks each side has given
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct ThreeCheck {
+    checks: [u8; 2],
+}
+
+impl ThreeCheck {
+    /// Number of checks that wins the game
+    pub const CHECKS_TO_WIN: u8 = 3;
+
+    /// Number of checks `colour` has given
+    pub fn checks(&self, colour: PieceColour) -> u8 {
+        self.checks[colour as usize]
+    }
+}
+
+impl Variant for ThreeCheck {
+    fn from_fen(fen: &Fen) -> Self {
+        Self {
+            checks: fen.checks.unwrap_or_default(),
+        }
+    }
+
+    fn to_fen(&self, fen: Fen) -> Fen {
+        Fen {
+            checks: Some(self.checks),
+            ..fen
+        }
+    }
+
+    fn after_move<B: LegalMoveGenerator>(&mut self, board: &B, _: PieceKind, _: B::Move) -> Result<(), ChessError> {
+        if board.in_check()? {
+            let checks = &mut self.checks[!board.turn() as usize];
+            *checks = checks.saturating_add(1);
+        }
+        Ok(())
+    }
+
+    fn decided_outcome<B: LegalMoveGenerator>(&self, _: &B) -> Option<GameOutcome> {
+        [PieceColour::White, PieceColour::Black]
+            .into_iter()
+            .find(|&colour| self.checks(colour) >= Self::CHECKS_TO_WIN)
+            .map(|winner| GameOutcome::Win {
+                winner,
+                reason: WinReason::ThreeCheck,
+            })
+    }
+
+    fn insufficient_material<B: ChessBoard>(&self, board: &B) -> bool {
+        board
+            .all_pieces()
+            .into_iter()
+            .all(|piece| piece.kind() == PieceKind::King)
+    }
+
+    fn is_dead_position<B: ChessBoard>(&self, board: &B) -> bool {
+        // Without pawns the board's dead positions are those lacking material to checkmate, which
+        // may still be enough to give check
+        if board.insufficient_material() {
+            self.insufficient_material(board)
+        } else {
+            board.is_dead_position()
+        }
+    }
+
+    fn hash(&self) -> u64 {
+        zobrist::checks_key(PieceColour::White, self.checks(PieceColour::White))
+            ^ zobrist::checks_key(PieceColour::Black, self.checks(PieceColour::Black))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enums::DrawReason;
+
+    fn play(board: &mut ThreeCheckBoard, moves: &[&str]) {
+        for pgn in moves {
+            let chess_move = board.disambiguate_move_pgn(pgn).unwrap();
+            board.move_piece_legal(chess_move).unwrap();
+        }
+    }
+
+    #[test]
+    fn counts_checks() {
+        let mut board = ThreeCheckBoard::starting_board();
+        assert_eq!(
+            board.to_fen().to_string(),
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1 +0+0"
+        );
+        play(&mut board, &["e4", "e5", "Bc4", "Nc6", "Bxf7+", "Kxf7", "Qh5+", "g6"]);
+        assert_eq!(board.variant().checks(PieceColour::White), 2);
+        assert_eq!(board.variant().checks(PieceColour::Black), 0);
+        assert_eq!(
+            board.to_fen().to_string(),
+            "r1bq1bnr/pppp1k1p/2n3p1/4p2Q/4P3/8/PPPP1PPP/RNB1K1NR w KQ - 0 5 +2+0"
+        );
+        assert_eq!(board.outcome().unwrap(), GameOutcome::Ongoing);
+
+        let undo = board.make_move(board.disambiguate_move_pgn("Qxg6+").unwrap()).unwrap();
+        assert_eq!(
+            board.outcome().unwrap(),
+            GameOutcome::Win {
+                winner: PieceColour::White,
+                reason: WinReason::ThreeCheck
+            }
+        );
+        assert_eq!(board.all_legal_moves().unwrap().into_iter().count(), 0);
+        board.unmake_move(undo);
+        assert_eq!(board.variant().checks(PieceColour::White), 2);
+    }
+
+    #[test]
+    fn loads_checks() {
+        let fen = "4k3/8/8/8/8/8/8/R3K3 w - - 0 1 +2+1";
+        let mut board = ThreeCheckBoard::from_fen(fen).unwrap();
+        assert_eq!(board.variant().checks(PieceColour::Black), 1);
+        assert_eq!(board.to_fen().to_string(), fen);
+        play(&mut board, &["Ra8+"]);
+        assert_eq!(
+            board.outcome().unwrap(),
+            GameOutcome::Win {
+                winner: PieceColour::White,
+                reason: WinReason::ThreeCheck
+            }
+        );
+    }
+
+    #[test]
+    fn repetition_with_checks() {
+        let mut board = ThreeCheckBoard::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+        let hash = board.hash();
+        play(&mut board, &["Ra8+", "Ke7", "Ra1", "Ke8"]);
+        // Same position on the board, but white has given a check since
+        assert_eq!(board.board().repetition_count(), 2);
+        assert_ne!(board.hash(), hash);
+        assert_eq!(board.repetition_count(), 1);
+        play(&mut board, &["Ra2", "Ke7", "Ra1", "Ke8"]);
+        assert_eq!(board.repetition_count(), 2);
+    }
+
+    #[test]
+    fn material() {
+        let board = ThreeCheckBoard::from_fen("4k3/8/8/8/8/8/8/4KB2 w - - 0 1").unwrap();
+        assert_eq!(board.outcome().unwrap(), GameOutcome::Ongoing);
+        let board = ThreeCheckBoard::from_fen("8/8/4k3/1p1p1p1p/1P1P1P1P/4K3/8/8 w - - 0 1").unwrap();
+        assert_eq!(board.outcome().unwrap(), GameOutcome::Draw(DrawReason::DeadPosition));
+        let board = ThreeCheckBoard::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(
+            board.outcome().unwrap(),
+            GameOutcome::Draw(DrawReason::InsufficientMaterial)
+        );
+    }
+}
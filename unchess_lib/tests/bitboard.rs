@@ -0,0 +1,86 @@
+//! Integration tests for the bitboard representation
+#![allow(clippy::tests_outside_test_module)]
+use unchess_lib::board::bitboard::BitBoard;
+use unchess_lib::board::piece_list::PieceListBoard;
+use unchess_lib::error::ChessError;
+use unchess_lib::notation::pgn_to_moves;
+use unchess_lib::simple_types::SimpleMove;
+use unchess_lib::traits::{ChessBoard as _, LegalMoveGenerator as _};
+
+const BYRNE_FISCHER_1956: &str = include_str!("pgn/byrne_fischer_1956.pgn");
+const BYRNE_FISCHER_1963: &str = include_str!("pgn/byrne_fischer_1963.pgn");
+const FISCHER_BENKO_1963: &str = include_str!("pgn/fischer_benko_1963.pgn");
+const FISCHER_MYAGMARSUREN_1967: &str = include_str!("pgn/fischer_myagmarsuren_1967.pgn");
+const FISCHER_SPASSKY_1972: &str = include_str!("pgn/fischer_spassky_1972.pgn");
+
+/// Replay game on both board representations, checking that they agree on every legal move
+fn test_pgn(pgn: &str) {
+    let mut board = BitBoard::starting_board();
+    let mut reference = PieceListBoard::starting_board();
+    let moves = pgn_to_moves(pgn).unwrap();
+    for chess_move in moves {
+        let mut legal_moves: Vec<SimpleMove> = board.all_legal_moves().unwrap().into_iter().map(Into::into).collect();
+        let mut reference_moves: Vec<SimpleMove> = reference.all_legal_moves().unwrap().into_iter().collect();
+        legal_moves.sort();
+        reference_moves.sort();
+        assert_eq!(legal_moves, reference_moves);
+
+        let unamb_move = board.disambiguate_move(chess_move).unwrap();
+        assert_eq!(
+            SimpleMove::from(unamb_move),
+            reference.disambiguate_move(chess_move).unwrap()
+        );
+        board.move_piece_legal(unamb_move).unwrap();
+        reference.move_piece_legal(unamb_move.into()).unwrap();
+        assert_eq!(board.to_fen().to_string(), reference.to_fen().to_string());
+        assert_eq!(board.outcome().unwrap(), reference.outcome().unwrap());
+    }
+    assert_eq!(board.state().unwrap(), reference.state().unwrap());
+}
+
+#[test]
+fn byrne_fischer_1956() {
+    test_pgn(BYRNE_FISCHER_1956);
+}
+
+#[test]
+fn byrne_fischer_1963() {
+    test_pgn(BYRNE_FISCHER_1963);
+}
+
+#[test]
+fn fischer_benko_1963() {
+    test_pgn(FISCHER_BENKO_1963);
+}
+
+#[test]
+fn fischer_myagmarsuren_1967() {
+    test_pgn(FISCHER_MYAGMARSUREN_1967);
+}
+
+#[test]
+fn fischer_spassky_1972() {
+    test_pgn(FISCHER_SPASSKY_1972);
+}
+
+#[test]
+fn illegal_positions() {
+    for fen in [
+        "8/8/8/8/8/8/8/4K3 w - - 0 1",
+        "4k3/8/8/8/8/8/8/P3K3 w - - 0 1",
+        "4k3/4Q3/8/8/8/8/8/4K3 w - - 0 1",
+        "4k3/8/8/8/8/8/8/4K3 w Q - 0 1",
+        "4k3/8/8/8/8/8/8/4K3 b - e3 0 1",
+    ] {
+        assert!(
+            matches!(BitBoard::from_fen(fen), Err(ChessError::InvalidPosition(_))),
+            "{fen}"
+        );
+        assert!(
+            matches!(PieceListBoard::from_fen(fen), Err(ChessError::InvalidPosition(_))),
+            "{fen}"
+        );
+        BitBoard::from_fen_unchecked(fen).unwrap();
+        PieceListBoard::from_fen_unchecked(fen).unwrap();
+    }
+}
@@ -0,0 +1,29 @@
+//! Reading and writing test suites in EPD
+#![allow(clippy::tests_outside_test_module)]
+use unchess_lib::board::bitboard::BitBoard;
+use unchess_lib::board::piece_list::PieceListBoard;
+use unchess_lib::notation::epd::Epd;
+use unchess_lib::traits::LegalMoveGenerator as _;
+
+const BRATKO_KOPEC: &str = include_str!("epd/bratko_kopec.epd");
+
+#[test]
+fn bratko_kopec() {
+    let records: Vec<_> = BRATKO_KOPEC
+        .lines()
+        .map(|line| Epd::from_epd_str(line).unwrap())
+        .collect();
+    assert_eq!(records.len(), 24);
+    for (i, (record, line)) in records.iter().zip(BRATKO_KOPEC.lines()).enumerate() {
+        assert_eq!(record.id(), Some(format!("BK.{:02}", i + 1).as_str()));
+        assert_eq!(record.as_epd_str(), line);
+        let best_moves = record.best_moves().unwrap();
+        assert!(!best_moves.is_empty());
+        let piece_list: PieceListBoard = record.board().unwrap();
+        let bitboard: BitBoard = record.board().unwrap();
+        for chess_move in best_moves {
+            piece_list.disambiguate_move(chess_move).unwrap();
+            bitboard.disambiguate_move(chess_move).unwrap();
+        }
+    }
+}
@@ -1,6 +1,11 @@
 //! Integration tests for parsing
 #![allow(clippy::tests_outside_test_module)]
-use unchess_lib::notation::pgn_to_moves;
+use std::io::BufReader;
+
+use unchess_lib::notation::{
+    pgn::{EXPORT_LINE_LENGTH, GameResult, Nag, PgnGame, PgnReader, SEVEN_TAG_ROSTER},
+    pgn_to_moves, replay,
+};
 
 const BYRNE_FISCHER_1956: &str = include_str!("pgn/byrne_fischer_1956.pgn");
 const BYRNE_FISCHER_1963: &str = include_str!("pgn/byrne_fischer_1963.pgn");
@@ -37,3 +42,136 @@ fn fischer_spassky_1972() {
     let moves = pgn_to_moves(FISCHER_SPASSKY_1972).unwrap();
     assert_eq!(moves.len(), 81);
 }
+
+#[test]
+fn byrne_fischer_1956_game() {
+    let game = PgnGame::from_pgn_str(BYRNE_FISCHER_1956).unwrap();
+    assert_eq!(game.tag("Event"), Some("Third Rosenwald Trophy"));
+    assert_eq!(game.tag("PlyCount"), Some("82"));
+    assert_eq!(game.tag("Opening"), None);
+    assert_eq!(game.result, GameResult::BlackWins);
+    assert_eq!(
+        game.mainline_moves().collect::<Vec<_>>(),
+        pgn_to_moves(BYRNE_FISCHER_1956).unwrap()
+    );
+    // 11. Bg5 is followed by a comment and 11... Na4 by the comment `{!}`
+    assert!(game.mainline.moves[20].comments[0].starts_with("11. Be2"));
+    assert_eq!(game.mainline.moves[21].comments, ["!"]);
+}
+
+#[test]
+fn all_games_parse() {
+    for pgn in [
+        BYRNE_FISCHER_1956,
+        BYRNE_FISCHER_1963,
+        FISCHER_BENKO_1963,
+        FISCHER_MYAGMARSUREN_1967,
+        FISCHER_SPASSKY_1972,
+    ] {
+        let game = PgnGame::from_pgn_str(pgn).unwrap();
+        assert_eq!(game.tag("Result"), Some(game.result.as_str()));
+        for name in SEVEN_TAG_ROSTER {
+            assert!(game.tag(name).is_some(), "{name}");
+        }
+    }
+}
+
+#[test]
+fn annotated_game() {
+    let pgn = "[Event \"Casual\"]\n\n1. e4 e5 2. Nf3 Nc6 3. Bb5 a6 {The Morphy Defence} (3... Nf6 $1 {Berlin} 4. O-O) 4. Ba4!? *";
+    let game = PgnGame::from_pgn_str(pgn).unwrap();
+    assert_eq!(game.result, GameResult::Unknown);
+    assert_eq!(pgn_to_moves(pgn).unwrap().len(), 7);
+    let a6 = &game.mainline.moves[5];
+    assert_eq!(a6.comments, ["The Morphy Defence"]);
+    let berlin = &a6.variations[0];
+    assert_eq!(berlin.moves.len(), 2);
+    assert_eq!(berlin.moves[0].nags, [Nag(1)]);
+    assert_eq!(berlin.moves[0].comments, ["Berlin"]);
+    assert_eq!(game.mainline.moves[6].nags, [Nag(5)]);
+}
+
+#[test]
+fn new_game_has_seven_tag_roster() {
+    let mut game = PgnGame::new();
+    let names: Vec<_> = game.tags.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(names, SEVEN_TAG_ROSTER);
+    game.set_tag("White", "Fischer");
+    game.set_tag("ECO", "D92");
+    assert_eq!(game.tag("White"), Some("Fischer"));
+    assert_eq!(game.tags.len(), 8);
+    PgnGame::from_pgn_str("1. e4 e5 1-0 trailing").unwrap_err();
+}
+
+#[test]
+fn export_round_trip() {
+    for pgn in [
+        BYRNE_FISCHER_1956,
+        BYRNE_FISCHER_1963,
+        FISCHER_BENKO_1963,
+        FISCHER_MYAGMARSUREN_1967,
+        FISCHER_SPASSKY_1972,
+    ] {
+        let game = PgnGame::from_pgn_str(pgn).unwrap();
+        let exported = game.to_string();
+        assert!(exported.lines().all(|line| line.len() <= EXPORT_LINE_LENGTH));
+        let reparsed = PgnGame::from_pgn_str(&exported).unwrap();
+        assert_eq!(reparsed.to_string(), exported);
+        for (name, value) in &game.tags {
+            assert_eq!(reparsed.tag(name), Some(value.as_str()));
+        }
+        assert_eq!(
+            reparsed.mainline_moves().collect::<Vec<_>>(),
+            game.mainline_moves().collect::<Vec<_>>()
+        );
+    }
+}
+
+#[test]
+fn read_database() {
+    let database = [
+        BYRNE_FISCHER_1956,
+        BYRNE_FISCHER_1963,
+        FISCHER_BENKO_1963,
+        FISCHER_MYAGMARSUREN_1967,
+        FISCHER_SPASSKY_1972,
+    ]
+    .join("\n");
+    let games: Vec<_> = PgnReader::new(BufReader::new(database.as_bytes()))
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(games.len(), 5);
+    assert_eq!(
+        games[4].tag("White"),
+        PgnGame::from_pgn_str(FISCHER_SPASSKY_1972).unwrap().tag("White")
+    );
+    assert_eq!(
+        games.iter().map(|game| game.mainline.moves.len()).sum::<usize>(),
+        82 + 42 + 41 + 61 + 81
+    );
+}
+
+#[test]
+fn replay_database() {
+    let database = [
+        BYRNE_FISCHER_1956,
+        "[Event \"Bad\"]\n\n1. e4 e5 2. Ke3 *\n",
+        FISCHER_SPASSKY_1972,
+    ]
+    .join("\n");
+    let replays = replay::replay_database(PgnReader::new(database.as_bytes()));
+    assert_eq!(replays.len(), 3);
+    let byrne_fischer = replays[0].as_ref().unwrap();
+    assert!(byrne_fischer.is_valid());
+    assert!(byrne_fischer.result_error.is_none());
+    assert_eq!(byrne_fischer.plies, 82);
+    assert_eq!(
+        byrne_fischer.final_fen.as_deref(),
+        Some("1Q6/5pk1/2p3p1/1p2N2p/1b5P/1bn5/2r3P1/2K5 w - - 16 42")
+    );
+    let bad = replays[1].as_ref().unwrap();
+    assert_eq!(bad.plies, 2);
+    assert!(!bad.is_valid());
+    assert!(bad.result_error.is_none());
+    assert!(replays[2].as_ref().unwrap().is_valid());
+}
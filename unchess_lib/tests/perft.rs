@@ -0,0 +1,189 @@
+//! Perft results for standard test positions
+//!
+//! Node counts from <https://www.chessprogramming.org/Perft_Results>, and for Chess960 from
+//! <https://www.chessprogramming.org/Chess960_Perft_Results>. Crazyhouse counts are those checked
+//! by Fairy-Stockfish's perft tests. Three-check and King of the Hill counts from the starting
+//! position are the standard ones, as neither variant can decide the game in four halfmoves.
+//!
+//! The other Three-check and King of the Hill counts haven't been checked against another engine.
+//! They are snapshots of this crate's own results, kept to catch changes in behaviour, and the
+//! tests using them are named `*_snapshot`.
+//!
+//! The piece list board is too slow to search as deep as the bitboard in a debug build, so it is
+//! only tested at shallower depths.
+#![allow(clippy::tests_outside_test_module)]
+use unchess_lib::board::bitboard::BitBoard;
+use unchess_lib::board::piece_list::PieceListBoard;
+use unchess_lib::traits::{ChessBoard as _, LegalMoveGenerator};
+use unchess_lib::variant::VariantBoard;
+use unchess_lib::variant::crazyhouse::CrazyhouseBoard;
+use unchess_lib::variant::king_of_the_hill::KingOfTheHillBoard;
+use unchess_lib::variant::three_check::{ThreeCheck, ThreeCheckBoard};
+
+const INITIAL: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+const POSITION_3: &str = "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1";
+const POSITION_4: &str = "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1";
+const POSITION_4_MIRRORED: &str = "r2q1rk1/pP1p2pp/Q4n2/bbp1p3/Np6/1B3NBn/pPPP1PPP/R3K2R b KQ - 0 1";
+const POSITION_5: &str = "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8";
+const POSITION_6: &str = "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10";
+const CRAZYHOUSE_POCKETS: &str = "2k5/8/8/8/8/8/8/4K3[QRBNPqrbnp] w - - 0 1";
+const THREE_CHECK_ROOK: &str = "4k3/8/8/8/8/8/8/R3K3 w - - 0 1 +2+0";
+const THREE_CHECK_RUY_LOPEZ: &str = "r1bqkbnr/pppp1ppp/2n5/1B2p3/4P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 2 3 +2+2";
+const KING_OF_THE_HILL_KINGS: &str = "8/2k5/8/8/8/3K4/8/8 w - - 0 1";
+const KING_OF_THE_HILL_BONGCLOUD: &str = "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPPKPPP/RNBQ1BNR b kq - 1 2";
+const CHESS960_1: &str = "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9";
+const CHESS960_2: &str = "2nnrbkr/p1qppppp/8/1ppb4/6PP/3PP3/PPP2P2/BQNNRBKR w HEhe - 1 9";
+const CHESS960_3: &str = "b1q1rrkb/pppppppp/3nn3/8/P7/1PPP4/4PPPP/BQNNRKRB w GE - 1 9";
+const CHESS960_4: &str = "qbbnnrkr/2pp2pp/p7/1p2pp2/8/P3PP2/1PPP1KPP/QBBNNR1R w hf - 0 9";
+const CHESS960_5: &str = "1nbbnrkr/p1p1ppp1/3p4/1p3P1p/3Pq2P/8/PPP1P1P1/QNBBNRKR w HFhf - 0 9";
+
+/// Check perft of `fen` at each depth against `nodes`, starting at depth 1
+fn test_perft<B: LegalMoveGenerator + Clone>(fen: &str, nodes: &[u64]) {
+    let board = B::from_fen(fen).unwrap();
+    for (depth, &expected) in (1..).zip(nodes) {
+        assert_eq!(board.perft(depth).unwrap(), expected, "{fen} at depth {depth}");
+    }
+}
+
+#[test]
+fn divide_sums_to_perft() {
+    let board = BitBoard::from_fen(KIWIPETE).unwrap();
+    let divided = board.perft_divide(2).unwrap();
+    assert_eq!(divided.len(), 48);
+    assert_eq!(divided.iter().map(|(_, nodes)| nodes).sum::<u64>(), 2039);
+}
+
+#[test]
+fn depth_zero() {
+    assert_eq!(BitBoard::starting_board().perft(0).unwrap(), 1);
+}
+
+#[test]
+fn bitboard_initial() {
+    test_perft::<BitBoard>(INITIAL, &[20, 400, 8902, 197_281]);
+}
+
+#[test]
+fn bitboard_kiwipete() {
+    test_perft::<BitBoard>(KIWIPETE, &[48, 2039, 97862]);
+}
+
+#[test]
+fn bitboard_position_3() {
+    test_perft::<BitBoard>(POSITION_3, &[14, 191, 2812, 43238, 674_624]);
+}
+
+#[test]
+fn bitboard_position_4() {
+    test_perft::<BitBoard>(POSITION_4, &[6, 264, 9467, 422_333]);
+}
+
+#[test]
+fn bitboard_position_4_mirrored() {
+    test_perft::<BitBoard>(POSITION_4_MIRRORED, &[6, 264, 9467, 422_333]);
+}
+
+#[test]
+fn bitboard_position_5() {
+    test_perft::<BitBoard>(POSITION_5, &[44, 1486, 62379]);
+}
+
+#[test]
+fn bitboard_position_6() {
+    test_perft::<BitBoard>(POSITION_6, &[46, 2079, 89890]);
+}
+
+#[test]
+fn piece_list_initial() {
+    test_perft::<PieceListBoard>(INITIAL, &[20, 400, 8902]);
+}
+
+#[test]
+fn piece_list_kiwipete() {
+    test_perft::<PieceListBoard>(KIWIPETE, &[48, 2039, 97862]);
+}
+
+#[test]
+fn piece_list_position_3() {
+    test_perft::<PieceListBoard>(POSITION_3, &[14, 191, 2812, 43238]);
+}
+
+#[test]
+fn piece_list_position_4() {
+    test_perft::<PieceListBoard>(POSITION_4, &[6, 264, 9467]);
+}
+
+#[test]
+fn piece_list_position_4_mirrored() {
+    test_perft::<PieceListBoard>(POSITION_4_MIRRORED, &[6, 264, 9467]);
+}
+
+#[test]
+fn piece_list_position_5() {
+    test_perft::<PieceListBoard>(POSITION_5, &[44, 1486, 62379]);
+}
+
+#[test]
+fn piece_list_position_6() {
+    test_perft::<PieceListBoard>(POSITION_6, &[46, 2079]);
+}
+
+#[test]
+fn bitboard_chess960() {
+    test_perft::<BitBoard>(CHESS960_1, &[21, 528, 12189, 326_672]);
+    test_perft::<BitBoard>(CHESS960_2, &[21, 807, 18002, 667_366]);
+    test_perft::<BitBoard>(CHESS960_3, &[20, 479, 10471, 273_318]);
+    test_perft::<BitBoard>(CHESS960_4, &[22, 593, 13440, 382_958]);
+    test_perft::<BitBoard>(CHESS960_5, &[28, 1120, 31058, 1_171_749]);
+}
+
+#[test]
+fn piece_list_chess960() {
+    test_perft::<PieceListBoard>(CHESS960_1, &[21, 528, 12189]);
+    test_perft::<PieceListBoard>(CHESS960_2, &[21, 807, 18002]);
+    test_perft::<PieceListBoard>(CHESS960_3, &[20, 479, 10471]);
+    test_perft::<PieceListBoard>(CHESS960_4, &[22, 593, 13440]);
+    test_perft::<PieceListBoard>(CHESS960_5, &[28, 1120, 31058]);
+}
+
+#[test]
+fn crazyhouse_initial() {
+    test_perft::<CrazyhouseBoard>(INITIAL, &[20, 400, 8902, 197_281]);
+}
+
+#[test]
+fn crazyhouse_pockets() {
+    test_perft::<CrazyhouseBoard>(CRAZYHOUSE_POCKETS, &[301, 75353]);
+}
+
+#[test]
+fn three_check_initial() {
+    test_perft::<ThreeCheckBoard>(INITIAL, &[20, 400, 8902, 197_281]);
+}
+
+#[test]
+fn three_check_rook_snapshot() {
+    test_perft::<ThreeCheckBoard>(THREE_CHECK_ROOK, &[15, 65, 1185, 6519]);
+}
+
+#[test]
+fn three_check_ruy_lopez_snapshot() {
+    test_perft::<ThreeCheckBoard>(THREE_CHECK_RUY_LOPEZ, &[32, 961, 30864]);
+    test_perft::<VariantBoard<PieceListBoard, ThreeCheck>>(THREE_CHECK_RUY_LOPEZ, &[32, 961]);
+}
+
+#[test]
+fn king_of_the_hill_initial() {
+    test_perft::<KingOfTheHillBoard>(INITIAL, &[20, 400, 8902, 197_281]);
+}
+
+#[test]
+fn king_of_the_hill_kings_snapshot() {
+    test_perft::<KingOfTheHillBoard>(KING_OF_THE_HILL_KINGS, &[8, 48, 377, 2295]);
+}
+
+#[test]
+fn king_of_the_hill_bongcloud_snapshot() {
+    test_perft::<KingOfTheHillBoard>(KING_OF_THE_HILL_BONGCLOUD, &[29, 663, 19944]);
+}
@@ -1,7 +1,9 @@
 //! Integration tests for parsing
 #![allow(clippy::tests_outside_test_module)]
 use unchess_lib::board::piece_list::PieceListBoard;
-use unchess_lib::notation::pgn_to_moves;
+use unchess_lib::enums::AmbiguousMove;
+use unchess_lib::error::ChessError;
+use unchess_lib::notation::{pgn::PgnGame, pgn_to_moves};
 use unchess_lib::traits::{ChessBoard as _, LegalMoveGenerator as _};
 
 const BYRNE_FISCHER_1956: &str = include_str!("pgn/byrne_fischer_1956.pgn");
@@ -11,12 +13,16 @@ const FISCHER_MYAGMARSUREN_1967: &str = include_str!("pgn/fischer_myagmarsuren_1
 const FISCHER_SPASSKY_1972: &str = include_str!("pgn/fischer_spassky_1972.pgn");
 
 fn test_pgn(pgn: &str) {
+    test_moves_from_start(&pgn_to_moves(pgn).unwrap()).unwrap();
+}
+
+fn test_moves_from_start(moves: &[AmbiguousMove]) -> Result<(), ChessError> {
     let mut board = PieceListBoard::starting_board();
-    let moves = pgn_to_moves(pgn).unwrap();
-    for chess_move in moves {
-        let unamb_move = board.disambiguate_move(chess_move).unwrap();
-        board.move_piece_legal(unamb_move).unwrap();
+    for &chess_move in moves {
+        let unamb_move = board.disambiguate_move(chess_move)?;
+        board.move_piece_legal(unamb_move)?;
     }
+    Ok(())
 }
 
 #[test]
@@ -43,3 +49,20 @@ fn fischer_myagmarsuren_1967() {
 fn fischer_spassky_1972() {
     test_pgn(FISCHER_SPASSKY_1972);
 }
+
+#[test]
+fn odds_game_from_fen() {
+    // Queen odds, so the king can step to d1
+    let pgn = "[Event \"Odds\"]\n[SetUp \"1\"]\n[FEN \"rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNB1KBNR w KQkq - 0 1\"]\n\n\
+        1. e4 e5 2. Kd1 Nc6 *";
+    let game = PgnGame::from_pgn_str(pgn).unwrap();
+    let board: PieceListBoard = game.final_board().unwrap();
+    assert_eq!(
+        board.to_fen().to_string(),
+        "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/8/PPPP1PPP/RNBK1BNR w kq - 2 3"
+    );
+    assert!(matches!(
+        test_moves_from_start(&pgn_to_moves(pgn).unwrap()),
+        Err(ChessError::ImpossibleMove(_))
+    ));
+}
@@ -0,0 +1,239 @@
+//! Writing moves in standard algebraic notation
+#![allow(clippy::tests_outside_test_module)]
+use std::fmt::Debug;
+
+use unchess_lib::board::bitboard::BitBoard;
+use unchess_lib::board::piece_list::PieceListBoard;
+use unchess_lib::enums::AmbiguousMove;
+use unchess_lib::error::ChessError;
+use unchess_lib::notation::pgn_to_moves;
+use unchess_lib::simple_types::SimpleMove;
+use unchess_lib::traits::{ChessBoard as _, ChessMove, LegalMoveGenerator};
+
+const BYRNE_FISCHER_1956: &str = include_str!("pgn/byrne_fischer_1956.pgn");
+const BYRNE_FISCHER_1963: &str = include_str!("pgn/byrne_fischer_1963.pgn");
+const FISCHER_BENKO_1963: &str = include_str!("pgn/fischer_benko_1963.pgn");
+const FISCHER_MYAGMARSUREN_1967: &str = include_str!("pgn/fischer_myagmarsuren_1967.pgn");
+const FISCHER_SPASSKY_1972: &str = include_str!("pgn/fischer_spassky_1972.pgn");
+
+const START_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+const POSITIONS: [&str; 7] = [
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+    "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+    "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+    "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9",
+    "rk5r/8/8/8/8/8/8/RK5R w HAha - 0 1",
+];
+
+/// Play through `pgn`, checking every move is written back exactly as it was given
+fn check_pgn<B: LegalMoveGenerator + Clone>(pgn: &str)
+where
+    B::Move: ChessMove<Square = B::Square> + PartialEq + Debug,
+{
+    let mut board = B::starting_board();
+    for chess_move in pgn_to_moves(pgn).unwrap() {
+        let unamb_move = board.disambiguate_move(chess_move).unwrap();
+        assert_eq!(board.ambiguous_move(unamb_move).unwrap(), chess_move);
+        assert_eq!(board.disambiguate_move_strict(chess_move).unwrap(), unamb_move);
+        board.move_piece_legal(unamb_move).unwrap();
+    }
+}
+
+/// Every legal move must be written so that it disambiguates back to the same move
+fn check_positions<B: LegalMoveGenerator + Clone>()
+where
+    B::Move: ChessMove<Square = B::Square> + PartialEq + Debug,
+{
+    for fen in POSITIONS {
+        let board = B::from_fen(fen).unwrap();
+        for chess_move in board.all_legal_moves().unwrap() {
+            let san = board.ambiguous_move_pgn(chess_move).unwrap();
+            assert_eq!(board.disambiguate_move_pgn(&san).unwrap(), chess_move, "{san} in {fen}");
+        }
+    }
+}
+
+/// SAN of the move `coordinates` on the board given by `fen`, checking both boards agree
+fn san(fen: &str, coordinates: &str) -> String {
+    let chess_move = SimpleMove::from_pgn_str(coordinates).unwrap();
+    let san = PieceListBoard::from_fen(fen)
+        .unwrap()
+        .ambiguous_move_pgn(chess_move)
+        .unwrap();
+    assert_eq!(
+        BitBoard::from_fen(fen)
+            .unwrap()
+            .ambiguous_move_pgn(chess_move.into())
+            .unwrap(),
+        san
+    );
+    san
+}
+
+/// Strictly disambiguate `san` on the board given by `fen`, checking both boards agree
+fn strict(fen: &str, san: &str) -> Result<(), ChessError> {
+    let chess_move = AmbiguousMove::try_from(san).unwrap();
+    let result = PieceListBoard::from_fen(fen)
+        .unwrap()
+        .disambiguate_move_strict(chess_move)
+        .map(|_| ());
+    let bitboard_result = BitBoard::from_fen(fen)
+        .unwrap()
+        .disambiguate_move_strict(chess_move)
+        .map(|_| ());
+    assert_eq!(format!("{result:?}"), format!("{bitboard_result:?}"), "{san} in {fen}");
+    result
+}
+
+#[test]
+fn famous_games() {
+    for pgn in [
+        BYRNE_FISCHER_1956,
+        BYRNE_FISCHER_1963,
+        FISCHER_BENKO_1963,
+        FISCHER_MYAGMARSUREN_1967,
+        FISCHER_SPASSKY_1972,
+    ] {
+        check_pgn::<PieceListBoard>(pgn);
+        check_pgn::<BitBoard>(pgn);
+    }
+}
+
+#[test]
+fn disambiguates_back() {
+    check_positions::<PieceListBoard>();
+    check_positions::<BitBoard>();
+}
+
+#[test]
+fn minimal_disambiguation() {
+    let fen = "7k/8/8/8/Q1Q5/8/Q7/7K w - - 0 1";
+    assert_eq!(san(fen, "a4b3"), "Qa4b3");
+    assert_eq!(san(fen, "c4b3"), "Qcb3");
+    assert_eq!(san(fen, "a2b3"), "Q2b3");
+    assert_eq!(san(fen, "c4d5"), "Qd5");
+}
+
+#[test]
+fn pawn_captures() {
+    let fen = "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3";
+    assert_eq!(san(fen, "e5f6"), "exf6");
+    assert_eq!(san(fen, "e5e6"), "e6");
+}
+
+#[test]
+fn promotion_and_check() {
+    assert_eq!(san("8/4P3/8/7k/8/8/8/4K3 w - - 0 1", "e7e8=Q"), "e8=Q+");
+    assert_eq!(san("8/4P3/8/7k/8/8/8/4K3 w - - 0 1", "e7e8=N"), "e8=N");
+    assert_eq!(san("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", "a1a8"), "Ra8#");
+    assert_eq!(san("5k2/8/8/8/8/8/8/4K2R w K - 0 1", "e1g1"), "O-O+");
+    assert_eq!(san("r3k3/8/8/8/8/8/8/4K3 b q - 0 1", "e8c8"), "O-O-O");
+}
+
+#[test]
+fn illegal_move() {
+    let board = PieceListBoard::starting_board();
+    let chess_move = SimpleMove::from_pgn_str("e2e5").unwrap();
+    assert!(matches!(
+        board.ambiguous_move(chess_move),
+        Err(ChessError::IllegalMove(illegal)) if illegal == chess_move
+    ));
+}
+
+#[test]
+fn strict_capture_markers() {
+    let fen = "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3";
+    strict(fen, "exf6").unwrap();
+    PieceListBoard::from_fen(fen)
+        .unwrap()
+        .disambiguate_move_pgn("ef6")
+        .unwrap();
+    assert!(matches!(
+        strict(fen, "ef6"),
+        Err(ChessError::MissingCaptureMarker { expected, .. }) if expected.as_pgn_str() == "exf6"
+    ));
+    assert!(matches!(
+        strict(fen, "Bxc4"),
+        Err(ChessError::UnexpectedCaptureMarker { expected, .. }) if expected.as_pgn_str() == "Bc4"
+    ));
+}
+
+#[test]
+fn strict_disambiguation() {
+    let fen = "7k/8/8/8/Q1Q5/8/Q7/7K w - - 0 1";
+    strict(fen, "Qcb3").unwrap();
+    strict(fen, "Qa4b3").unwrap();
+    for san in ["Q4d5", "Qc4b3", "Qcd5"] {
+        assert!(
+            matches!(strict(fen, san), Err(ChessError::OverDisambiguated { .. })),
+            "{san}"
+        );
+    }
+    assert!(matches!(strict(fen, "Qab3"), Err(ChessError::AmbiguousMove(_))));
+    assert!(matches!(
+        strict(START_FEN, "Ngf3"),
+        Err(ChessError::OverDisambiguated { expected, .. }) if expected.as_pgn_str() == "Nf3"
+    ));
+}
+
+#[test]
+fn strict_check_suffixes() {
+    let promotion = "8/4P3/8/7k/8/8/8/4K3 w - - 0 1";
+    strict(promotion, "e8=Q+").unwrap();
+    assert!(matches!(
+        strict(promotion, "e8=Q"),
+        Err(ChessError::MissingCheckSuffix { .. })
+    ));
+    assert!(matches!(
+        strict(promotion, "e8=Q#"),
+        Err(ChessError::WrongCheckSuffix { .. })
+    ));
+    assert!(matches!(
+        strict(promotion, "e8=N+"),
+        Err(ChessError::WrongCheckSuffix { .. })
+    ));
+
+    let mate = "6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1";
+    strict(mate, "Ra8#").unwrap();
+    assert!(matches!(
+        strict(mate, "Ra8+"),
+        Err(ChessError::WrongCheckSuffix { expected, .. }) if expected.as_pgn_str() == "Ra8#"
+    ));
+
+    let castle = "5k2/8/8/8/8/8/8/4K2R w K - 0 1";
+    strict(castle, "O-O+").unwrap();
+    assert!(matches!(
+        strict(castle, "O-O"),
+        Err(ChessError::MissingCheckSuffix { .. })
+    ));
+}
+
+#[test]
+fn strict_impossible_moves() {
+    assert!(matches!(strict(START_FEN, "Nd4"), Err(ChessError::ImpossibleMove(_))));
+    assert!(matches!(
+        strict("r3k3/8/8/8/8/8/8/4K3 b q - 0 1", "O-O"),
+        Err(ChessError::ImpossibleMove(_))
+    ));
+}
+
+#[test]
+fn chess960_castling() {
+    let fen = "rk5r/8/8/8/8/8/8/RK5R w HAha - 0 1";
+    assert_eq!(san(fen, "b1h1"), "O-O");
+    assert_eq!(san(fen, "b1a1"), "O-O-O");
+    assert_eq!(san("rk5r/8/8/8/8/8/8/R4KRR w GA - 0 1", "f1g1"), "O-O");
+
+    let mut piece_list = PieceListBoard::from_fen(fen).unwrap();
+    let mut bitboard = BitBoard::from_fen(fen).unwrap();
+    for san in ["O-O", "O-O-O"] {
+        let chess_move = piece_list.disambiguate_move_pgn(san).unwrap();
+        piece_list.move_piece_legal(chess_move).unwrap();
+        bitboard.move_piece_legal(chess_move.into()).unwrap();
+    }
+    assert_eq!(piece_list.to_fen().to_string(), "2kr3r/8/8/8/8/8/8/R4RK1 w - - 2 2");
+    assert_eq!(bitboard.to_fen(), piece_list.to_fen());
+}
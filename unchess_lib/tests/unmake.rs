@@ -0,0 +1,44 @@
+//! Unmaking moves restores the exact board state
+#![allow(clippy::tests_outside_test_module)]
+use std::fmt::Debug;
+
+use unchess_lib::board::bitboard::BitBoard;
+use unchess_lib::board::piece_list::PieceListBoard;
+use unchess_lib::traits::{ChessBoard as _, ChessMove as _, LegalMoveGenerator};
+
+const POSITIONS: [&str; 5] = [
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+    "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+    "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+];
+
+/// Make and unmake every line of legal moves `depth` deep, checking the board after each unmake
+fn check_unmake<B: LegalMoveGenerator + Clone + PartialEq + Debug>(board: &mut B, depth: u32) {
+    if depth == 0 {
+        return;
+    }
+    let moves: Vec<B::Move> = board.all_legal_moves().unwrap().into_iter().collect();
+    for chess_move in moves {
+        let before = board.clone();
+        let undo = board.make_move(chess_move).unwrap();
+        check_unmake(board, depth - 1);
+        board.unmake_move(undo);
+        assert_eq!(*board, before, "unmaking {}", chess_move.as_str());
+    }
+}
+
+#[test]
+fn bitboard_unmake() {
+    for fen in POSITIONS {
+        check_unmake(&mut BitBoard::from_fen(fen).unwrap(), 2);
+    }
+}
+
+#[test]
+fn piece_list_unmake() {
+    for fen in POSITIONS {
+        check_unmake(&mut PieceListBoard::from_fen(fen).unwrap(), 2);
+    }
+}
@@ -0,0 +1,73 @@
+//! Incrementally updated Zobrist hashes match hashes calculated from scratch
+#![allow(clippy::tests_outside_test_module)]
+use unchess_lib::board::bitboard::{BitBoard, BitMove};
+use unchess_lib::board::piece_list::PieceListBoard;
+use unchess_lib::traits::{ChessBoard as _, LegalMoveGenerator as _};
+
+const POSITIONS: [&str; 4] = [
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+    "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+    "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+];
+
+/// Check the hash of every position `depth` moves deep against the hash of the same position
+/// loaded from FEN, on both board representations
+fn check_hashes(bitboard: &mut BitBoard, piece_list: &mut PieceListBoard, depth: u32) {
+    let fen = bitboard.to_fen().to_string();
+    assert_eq!(bitboard.hash(), BitBoard::from_fen(&fen).unwrap().hash(), "{fen}");
+    assert_eq!(piece_list.hash(), bitboard.hash(), "{fen}");
+    if depth == 0 {
+        return;
+    }
+    let moves: Vec<_> = bitboard.all_legal_moves().unwrap().into_iter().collect();
+    for chess_move in moves {
+        let bitboard_undo = bitboard.make_move(chess_move).unwrap();
+        let piece_list_undo = piece_list.make_move(chess_move.into()).unwrap();
+        check_hashes(bitboard, piece_list, depth - 1);
+        piece_list.unmake_move(piece_list_undo);
+        bitboard.unmake_move(bitboard_undo);
+    }
+}
+
+#[test]
+fn incremental_hashes() {
+    for fen in POSITIONS {
+        check_hashes(
+            &mut BitBoard::from_fen(fen).unwrap(),
+            &mut PieceListBoard::from_fen(fen).unwrap(),
+            2,
+        );
+    }
+}
+
+#[test]
+fn transposition() {
+    let mut board = BitBoard::starting_board();
+    let mut transposed = BitBoard::starting_board();
+    for chess_move in ["g1f3", "g8f6", "b1c3"] {
+        board
+            .move_piece_legal(BitMove::from_pgn_str(chess_move).unwrap())
+            .unwrap();
+    }
+    for chess_move in ["b1c3", "g8f6", "g1f3"] {
+        transposed
+            .move_piece_legal(BitMove::from_pgn_str(chess_move).unwrap())
+            .unwrap();
+    }
+    assert_eq!(board.hash(), transposed.hash());
+}
+
+#[test]
+fn side_to_move() {
+    let white = BitBoard::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    let black = BitBoard::from_fen("4k3/8/8/8/8/8/8/4K3 b - - 0 1").unwrap();
+    assert_ne!(white.hash(), black.hash());
+}
+
+#[test]
+fn castling_rights() {
+    let with_rights = BitBoard::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+    let without_rights = BitBoard::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQk - 0 1").unwrap();
+    assert_ne!(with_rights.hash(), without_rights.hash());
+}